@@ -0,0 +1,25 @@
+#!/bin/sh
+# Download the Leaflet assets that are embedded with the `embed-leaflet` feature
+# to this directory and verify them against the hashes in `src/leaflet_assets.rs`.
+# The build script reads them from here (or from `LEAFLET_ASSETS_DIR`).
+set -eu
+
+VERSION=1.9.4
+
+cd "$(dirname "$0")"
+
+fetch() {
+  file=$1
+  integrity=$2
+  curl --silent --show-error --fail --location --output "$file" \
+    "https://unpkg.com/leaflet@$VERSION/dist/$file"
+  actual=$(openssl dgst -sha256 -binary "$file" | openssl base64 -A)
+  if [ "$actual" != "$integrity" ]; then
+    echo "Integrity check of $file failed" >&2
+    rm "$file"
+    exit 1
+  fi
+}
+
+fetch leaflet.js "20nQCchB9co0qIjJZRGuk2/Z9VM+kNiyxNV1lvTlZBo="
+fetch leaflet.css "p4NxAoJBhIIN+hmNHrzRCf9tD/miZyoHS5obTRR9BMY="
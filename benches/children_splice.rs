@@ -0,0 +1,63 @@
+//! Native benchmarks of the splice that is used to rebuild the map children.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use xilem_leaflet::splice::VecSplice;
+
+const CHILDREN_COUNT: usize = 50_000;
+
+/// Scattered insertions and deletions
+/// (every 7th child is removed and after every 11th child a new one is inserted).
+fn rebuild(children: &mut Vec<usize>, scratch: &mut Vec<usize>) {
+    let mut splice = VecSplice::new(children, scratch);
+    for i in 0..CHILDREN_COUNT {
+        if i % 7 == 0 {
+            black_box(splice.delete());
+        } else {
+            *splice.mutate() += 1;
+        }
+        if i % 11 == 0 {
+            splice.insert(i);
+        }
+    }
+}
+
+/// The previous implementation that shifts the elements on each insertion or deletion.
+fn rebuild_naive(children: &mut Vec<usize>) {
+    let mut idx = 0;
+    for i in 0..CHILDREN_COUNT {
+        if i % 7 == 0 {
+            black_box(children.remove(idx));
+        } else {
+            children[idx] += 1;
+            idx += 1;
+        }
+        if i % 11 == 0 {
+            children.insert(idx, i);
+            idx += 1;
+        }
+    }
+}
+
+fn children_splice(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rebuild 50k children");
+    group.bench_function("VecSplice", |b| {
+        let mut scratch = Vec::with_capacity(CHILDREN_COUNT);
+        b.iter_batched_ref(
+            || (0..CHILDREN_COUNT).collect::<Vec<_>>(),
+            |children| rebuild(children, &mut scratch),
+            BatchSize::LargeInput,
+        );
+    });
+    group.sample_size(10);
+    group.bench_function("naive", |b| {
+        b.iter_batched_ref(
+            || (0..CHILDREN_COUNT).collect::<Vec<_>>(),
+            rebuild_naive,
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+criterion_group!(benches, children_splice);
+criterion_main!(benches);
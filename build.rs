@@ -0,0 +1,65 @@
+//! Provide the Leaflet assets that are embedded with the `embed-leaflet` feature.
+
+fn main() {
+    #[cfg(feature = "embed-leaflet")]
+    embed_leaflet::provide_assets();
+}
+
+#[cfg(feature = "embed-leaflet")]
+mod embed_leaflet {
+    use std::{env, fs, path::PathBuf};
+
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+    use sha2::{Digest as _, Sha256};
+
+    /// A directory with the assets that is used instead of `assets/leaflet`.
+    const ASSETS_DIR: &str = "LEAFLET_ASSETS_DIR";
+
+    /// The directory that `assets/leaflet/fetch.sh` downloads the assets to.
+    const DEFAULT_ASSETS_DIR: &str = "assets/leaflet";
+
+    /// The files and their subresource integrity hashes
+    /// (the same as `SCRIPT_INTEGRITY` and `STYLESHEET_INTEGRITY` in `src/leaflet_assets.rs`).
+    const ASSETS: [(&str, &str); 2] = [
+        (
+            "leaflet.js",
+            "sha256-20nQCchB9co0qIjJZRGuk2/Z9VM+kNiyxNV1lvTlZBo=",
+        ),
+        (
+            "leaflet.css",
+            "sha256-p4NxAoJBhIIN+hmNHrzRCf9tD/miZyoHS5obTRR9BMY=",
+        ),
+    ];
+
+    pub(super) fn provide_assets() {
+        println!("cargo::rerun-if-changed=build.rs");
+        println!("cargo::rerun-if-env-changed={ASSETS_DIR}");
+        let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR is not set"));
+        let assets_dir = env::var_os(ASSETS_DIR).map_or_else(
+            || {
+                let manifest_dir =
+                    env::var_os("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is not set");
+                PathBuf::from(manifest_dir).join(DEFAULT_ASSETS_DIR)
+            },
+            PathBuf::from,
+        );
+        for (file, integrity) in ASSETS {
+            let path = assets_dir.join(file);
+            println!("cargo::rerun-if-changed={}", path.display());
+            let content = fs::read(&path).unwrap_or_else(|err| {
+                panic!(
+                    "Unable to read {} (run {DEFAULT_ASSETS_DIR}/fetch.sh or set {ASSETS_DIR}): {err}",
+                    path.display()
+                )
+            });
+            let actual = format!("sha256-{}", STANDARD.encode(Sha256::digest(&content)));
+            assert!(
+                actual == integrity,
+                "Integrity check of {file} failed (expected {integrity}, got {actual})"
+            );
+            let path = out_dir.join(file);
+            fs::write(&path, content)
+                .unwrap_or_else(|err| panic!("Unable to write {}: {err}", path.display()));
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use xilem_leaflet::{map, marker, tile_layer};
+use xilem_leaflet::{keyed, map, marker, tile_layer, LatLng, MapHandle};
 use xilem_web::{
     document_body, elements::html, input_event_target_value, interfaces::Element, modifiers::style,
     App,
@@ -7,8 +7,24 @@ use xilem_web::{
 struct AppState {
     zoom_input: Option<String>,
     zoom: f64,
-    center: (f64, f64),
-    markers: Vec<(f64, f64)>,
+    center: LatLng,
+    markers: Vec<MarkerItem>,
+    next_marker_id: u32,
+    map_handle: Option<MapHandle>,
+}
+
+/// A marker with a stable ID to key its view.
+struct MarkerItem {
+    id: u32,
+    position: LatLng,
+}
+
+impl AppState {
+    fn add_marker(&mut self, position: LatLng) {
+        let id = self.next_marker_id;
+        self.next_marker_id += 1;
+        self.markers.push(MarkerItem { id, position });
+    }
 }
 
 impl Default for AppState {
@@ -16,8 +32,13 @@ impl Default for AppState {
         Self {
             zoom_input: None,
             zoom: 12.0,
-            center: (48.64, 9.46),
-            markers: vec![(48.64, 9.46)],
+            center: LatLng::new(48.64, 9.46),
+            markers: vec![MarkerItem {
+                id: 0,
+                position: LatLng::new(48.64, 9.46),
+            }],
+            next_marker_id: 1,
+            map_handle: None,
         }
     }
 }
@@ -25,11 +46,7 @@ impl Default for AppState {
 const TILE_LAYER_URL: &str = "https://{s}.tile.openstreetmap.org/{z}/{x}/{y}.png";
 
 fn app_logic(state: &mut AppState) -> impl Element<AppState> {
-    let markers: Vec<_> = state
-        .markers
-        .iter()
-        .map(|(lat, lng)| marker(*lat, *lng))
-        .collect();
+    let markers = keyed(&state.markers, |item| item.id, |item| marker(item.position));
     html::div((
         html::label((
             "Zoom:",
@@ -47,9 +64,23 @@ fn app_logic(state: &mut AppState) -> impl Element<AppState> {
                     };
                 }),
         )),
+        html::button("Reset view").on_click(|state: &mut AppState, _| {
+            if let Some(handle) = &state.map_handle {
+                let AppState { center, zoom, .. } = AppState::default();
+                handle.set_view(center, zoom);
+            }
+        }),
+        html::button("Remove oldest marker").on_click(|state: &mut AppState, _| {
+            if !state.markers.is_empty() {
+                state.markers.remove(0);
+            }
+        }),
         map((tile_layer(TILE_LAYER_URL), markers))
-            .center(state.center.0, state.center.1)
+            .center(state.center)
             .zoom(state.zoom)
+            .on_ready(|state: &mut AppState, handle| {
+                state.map_handle = Some(handle);
+            })
             .on_zoom_end(|state: &mut AppState, map, _ev| {
                 let zoom = map.get_zoom();
                 log::debug!("Zoom has changed to {zoom}");
@@ -60,8 +91,7 @@ fn app_logic(state: &mut AppState) -> impl Element<AppState> {
                 log::debug!("Bbox has changed to {bounds:?}");
             })
             .on_mouse_click(|state: &mut AppState, ev| {
-                let lat_lng = ev.lat_lng();
-                state.markers.push((lat_lng.lat(), lat_lng.lng()));
+                state.add_marker(LatLng::from(&ev.lat_lng()));
             }),
     ))
     .style(style("width", "100%"))
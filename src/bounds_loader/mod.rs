@@ -0,0 +1,322 @@
+use std::{cell::RefCell, fmt, future::Future, marker::PhantomData, rc::Rc};
+
+use wasm_bindgen_futures::spawn_local;
+use web_sys::wasm_bindgen::{closure::Closure, JsCast as _, UnwrapThrowExt as _};
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker as _},
+    DynMessage, MessageThunk,
+};
+
+use crate::{
+    map::{is_loaded, EventListener},
+    LatLngBounds, MapChildElement, MapCtx,
+};
+
+mod scheduler;
+
+use self::scheduler::Scheduler;
+
+/// Distinctive ID for better debugging
+const BOUNDS_LOADER_ID: ViewId = ViewId::new(23678);
+
+/// Default delay in milliseconds (see [`BoundsLoader::debounce`]).
+const DEFAULT_DEBOUNCE: u32 = 300;
+
+/// The visible area of the map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Viewport {
+    pub bounds: LatLngBounds,
+    pub zoom: f64,
+}
+
+/// Data that has been loaded for a [`Viewport`] (see [`bounds_loader`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundsData<T, E> {
+    /// The most recently loaded data
+    /// (which is kept while the data for another viewport is loading).
+    pub data: Option<T>,
+    /// The viewport that `data` has been loaded for.
+    pub viewport: Option<Viewport>,
+    /// The data for the current viewport is being loaded.
+    ///
+    /// It stays `true` if the loader is removed while loading,
+    /// since the pending request is cancelled without access to the state.
+    pub loading: bool,
+    /// The error of the most recent request.
+    pub error: Option<E>,
+}
+
+impl<T, E> Default for BoundsData<T, E> {
+    fn default() -> Self {
+        Self {
+            data: None,
+            viewport: None,
+            loading: false,
+            error: None,
+        }
+    }
+}
+
+impl<T, E> BoundsData<T, E> {
+    fn finish(&mut self, viewport: Viewport, result: Result<T, E>) {
+        self.loading = false;
+        match result {
+            Ok(data) => {
+                self.data = Some(data);
+                self.viewport = Some(viewport);
+                self.error = None;
+            }
+            Err(err) => {
+                self.error = Some(err);
+            }
+        }
+    }
+}
+
+/// Load data whenever the visible area of the map has changed.
+///
+/// After the map has been moved or zoomed and then stayed still for the
+/// [debounce](BoundsLoader::debounce) delay, `fetch` is called with the current
+/// [`Viewport`]. A request that is still pending at that time is cancelled
+/// (i.e. its future is dropped), so only the result for the most recent
+/// viewport is stored in the [`BoundsData`] returned by `data`.
+/// A pending request is also cancelled as soon as the map starts moving again.
+///
+/// The loader doesn't create any layers. Render the data with other children of the map.
+pub fn bounds_loader<State, T, E, F, Fut, D>(fetch: F, data: D) -> BoundsLoader<State, T, E, F, D>
+where
+    F: Fn(Viewport) -> Fut + 'static,
+    Fut: Future<Output = Result<T, E>> + 'static,
+    D: Fn(&mut State) -> &mut BoundsData<T, E> + 'static,
+{
+    BoundsLoader {
+        fetch,
+        data,
+        debounce: DEFAULT_DEBOUNCE,
+        phantom: PhantomData,
+    }
+}
+
+pub struct BoundsLoader<State, T, E, F, D> {
+    fetch: F,
+    data: D,
+    debounce: u32,
+    phantom: PhantomData<fn() -> (State, T, E)>,
+}
+
+impl<State, T, E, F, D> BoundsLoader<State, T, E, F, D> {
+    /// The time in milliseconds the map must not move before the data is loaded.
+    #[must_use]
+    pub const fn debounce(mut self, millis: u32) -> Self {
+        self.debounce = millis;
+        self
+    }
+}
+
+impl<State, T, E, F, D> ViewMarker for BoundsLoader<State, T, E, F, D> {}
+
+#[derive(Debug)]
+struct ViewportSettled(Viewport);
+
+struct Loaded<T, E> {
+    request_id: u64,
+    viewport: Viewport,
+    result: Result<T, E>,
+}
+
+impl<T, E> fmt::Debug for Loaded<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Loaded")
+            .field("request_id", &self.request_id)
+            .field("viewport", &self.viewport)
+            .field("ok", &self.result.is_ok())
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct BoundsLoaderViewState {
+    loader: Rc<RefCell<Loader>>,
+    listeners: Vec<EventListener<leaflet::Map>>,
+}
+
+struct Loader {
+    map: leaflet::Map,
+    thunk: Rc<MessageThunk>,
+    debounce: u32,
+    scheduler: Scheduler<Timeout>,
+}
+
+impl Loader {
+    fn viewport(&self) -> Viewport {
+        Viewport {
+            bounds: self.map.get_bounds().into(),
+            zoom: self.map.get_zoom(),
+        }
+    }
+
+    /// Report the viewport after the debounce delay,
+    /// unless this is called again before.
+    ///
+    /// The pending request is cancelled, since the viewport has changed.
+    fn schedule(this: &Rc<RefCell<Self>>) {
+        let weak = Rc::downgrade(this);
+        let mut loader = this.borrow_mut();
+        let timeout = Timeout::new(loader.debounce, move || {
+            let Some(this) = weak.upgrade() else {
+                return;
+            };
+            // The borrow must be released before the message is handled.
+            let (viewport, thunk) = {
+                let loader = this.borrow();
+                (loader.viewport(), Rc::clone(&loader.thunk))
+            };
+            thunk.push_message(ViewportSettled(viewport));
+        });
+        loader.scheduler.moved(timeout);
+    }
+}
+
+/// A timeout that is cleared when it is dropped.
+struct Timeout {
+    handle: i32,
+    _closure: Closure<dyn FnMut()>,
+}
+
+impl Timeout {
+    fn new(millis: u32, callback: impl FnOnce() + 'static) -> Self {
+        let closure = Closure::once(callback);
+        let handle = web_sys::window()
+            .unwrap_throw()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                i32::try_from(millis).unwrap_or(i32::MAX),
+            )
+            .unwrap_throw();
+        Self {
+            handle,
+            _closure: closure,
+        }
+    }
+}
+
+impl Drop for Timeout {
+    fn drop(&mut self) {
+        if let Some(window) = web_sys::window() {
+            window.clear_timeout_with_handle(self.handle);
+        }
+    }
+}
+
+impl<State, Action, T, E, F, Fut, D> View<State, Action, MapCtx, DynMessage>
+    for BoundsLoader<State, T, E, F, D>
+where
+    State: 'static,
+    Action: 'static,
+    T: 'static,
+    E: 'static,
+    F: Fn(Viewport) -> Fut + 'static,
+    Fut: Future<Output = Result<T, E>> + 'static,
+    D: Fn(&mut State) -> &mut BoundsData<T, E> + 'static,
+{
+    type Element = MapChildElement;
+
+    type ViewState = BoundsLoaderViewState;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        ctx.with_id(BOUNDS_LOADER_ID, |ctx| {
+            let map = ctx.map();
+            let loader = Rc::new(RefCell::new(Loader {
+                map: map.clone(),
+                thunk: Rc::new(ctx.message_thunk()),
+                debounce: self.debounce,
+                scheduler: Scheduler::new(),
+            }));
+            if is_loaded(map) {
+                Loader::schedule(&loader);
+            }
+            let listeners = vec![
+                {
+                    let loader = Rc::clone(&loader);
+                    // The viewport of the pending request is outdated.
+                    EventListener::new(map, "movestart", move |_: leaflet::Event| {
+                        loader.borrow_mut().scheduler.cancel();
+                    })
+                },
+                {
+                    let loader = Rc::clone(&loader);
+                    EventListener::new(map, "moveend", move |_: leaflet::Event| {
+                        Loader::schedule(&loader);
+                    })
+                },
+            ];
+            let view_state = BoundsLoaderViewState { loader, listeners };
+            (MapChildElement::Event, view_state)
+        })
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx,
+        _: Mut<Self::Element>,
+    ) {
+        ctx.with_id(BOUNDS_LOADER_ID, |_| {
+            if prev.debounce != self.debounce {
+                view_state.loader.borrow_mut().debounce = self.debounce;
+            }
+        });
+    }
+
+    fn teardown(&self, view_state: &mut Self::ViewState, ctx: &mut MapCtx, _: Mut<Self::Element>) {
+        ctx.with_id(BOUNDS_LOADER_ID, |_| {
+            view_state.listeners.clear();
+            view_state.loader.borrow_mut().scheduler.cancel();
+        });
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        state: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        debug_assert!(id_path.len() == 1 && id_path[0] == BOUNDS_LOADER_ID);
+        match message.downcast::<ViewportSettled>() {
+            Ok(settled) => {
+                let ViewportSettled(viewport) = *settled;
+                (self.data)(state).loading = true;
+                let future = (self.fetch)(viewport);
+                let (request_id, request, thunk) = {
+                    let mut loader = view_state.loader.borrow_mut();
+                    let (request_id, request) = loader.scheduler.request(future);
+                    (request_id, request, Rc::clone(&loader.thunk))
+                };
+                spawn_local(async move {
+                    if let Some(result) = request.await {
+                        thunk.push_message(Loaded {
+                            request_id,
+                            viewport,
+                            result,
+                        });
+                    }
+                });
+            }
+            Err(message) => {
+                let loaded: Loaded<T, E> = *message.downcast().unwrap_throw();
+                // The result of a cancelled request is ignored.
+                if view_state
+                    .loader
+                    .borrow_mut()
+                    .scheduler
+                    .finish(loaded.request_id)
+                {
+                    (self.data)(state).finish(loaded.viewport, loaded.result);
+                }
+            }
+        }
+        MessageResult::Nop
+    }
+}
@@ -0,0 +1,246 @@
+//! Debouncing and cancellation of the requests of the [`bounds_loader`](super::bounds_loader),
+//! independent of the map and the browser.
+
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+/// Keeps track of the debounce timer and the pending request.
+///
+/// `Timer` is the handle of a timer that is cleared when it is dropped.
+pub(super) struct Scheduler<Timer> {
+    timer: Option<Timer>,
+    request: Option<Request>,
+    next_request_id: u64,
+}
+
+/// A pending request that is cancelled when it is dropped.
+struct Request {
+    id: u64,
+    _cancel: CancelOnDrop,
+}
+
+impl<Timer> Scheduler<Timer> {
+    pub(super) const fn new() -> Self {
+        Self {
+            timer: None,
+            request: None,
+            next_request_id: 0,
+        }
+    }
+
+    /// The map has been moved: Restart the debounce with the new timer
+    /// and cancel the pending request, whose viewport is outdated.
+    pub(super) fn moved(&mut self, timer: Timer) {
+        self.request = None;
+        // The previous timer is cleared when it is dropped.
+        self.timer = Some(timer);
+    }
+
+    /// Cancel the pending request (if any) and register a new one.
+    ///
+    /// The returned future resolves to `None` as soon as the request has been cancelled.
+    ///
+    /// The timer is kept, since the request is usually started by its callback.
+    pub(super) fn request<Fut: Future>(&mut self, future: Fut) -> (u64, Cancellable<Fut>) {
+        let id = self.next_request_id;
+        self.next_request_id += 1;
+        let flag = Rc::new(CancelFlag::default());
+        self.request = Some(Request {
+            id,
+            _cancel: CancelOnDrop(Rc::clone(&flag)),
+        });
+        let cancellable = Cancellable {
+            future: Box::pin(future),
+            flag,
+        };
+        (id, cancellable)
+    }
+
+    /// Check whether the request is still pending and unregister it.
+    pub(super) fn finish(&mut self, id: u64) -> bool {
+        if self
+            .request
+            .as_ref()
+            .is_some_and(|request| request.id == id)
+        {
+            self.request = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Clear the timer and cancel the pending request.
+    pub(super) fn cancel(&mut self) {
+        self.timer = None;
+        self.request = None;
+    }
+}
+
+#[derive(Default)]
+struct CancelFlag {
+    cancelled: Cell<bool>,
+    waker: RefCell<Option<Waker>>,
+}
+
+struct CancelOnDrop(Rc<CancelFlag>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancelled.set(true);
+        if let Some(waker) = self.0.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A future that resolves to `None` as soon as it has been cancelled
+/// (and drops the inner future then).
+pub(super) struct Cancellable<Fut> {
+    future: Pin<Box<Fut>>,
+    flag: Rc<CancelFlag>,
+}
+
+impl<Fut: Future> Future for Cancellable<Fut> {
+    type Output = Option<Fut::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.flag.cancelled.get() {
+            return Poll::Ready(None);
+        }
+        *self.flag.waker.borrow_mut() = Some(cx.waker().clone());
+        self.future.as_mut().poll(cx).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::Wake,
+    };
+
+    use super::*;
+
+    /// A request whose response is set by the test.
+    #[derive(Default)]
+    struct FakeFetch {
+        response: Rc<Cell<Option<u32>>>,
+    }
+
+    impl Future for FakeFetch {
+        type Output = u32;
+
+        fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<u32> {
+            self.response.take().map_or(Poll::Pending, Poll::Ready)
+        }
+    }
+
+    /// A timer that records whether it has been cleared (dropped).
+    struct FakeTimer(Rc<Cell<bool>>);
+
+    impl Drop for FakeTimer {
+        fn drop(&mut self) {
+            self.0.set(true);
+        }
+    }
+
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn poll<Fut: Future>(
+        future: &mut Cancellable<Fut>,
+        waker: &Arc<CountingWaker>,
+    ) -> Poll<Option<Fut::Output>> {
+        let waker = Waker::from(Arc::clone(waker));
+        Pin::new(future).poll(&mut Context::from_waker(&waker))
+    }
+
+    #[test]
+    fn finish_the_pending_request() {
+        let waker = Arc::default();
+        let mut scheduler = Scheduler::<FakeTimer>::new();
+        let fetch = FakeFetch::default();
+        let response = Rc::clone(&fetch.response);
+        let (id, mut future) = scheduler.request(fetch);
+        assert_eq!(poll(&mut future, &waker), Poll::Pending);
+        response.set(Some(42));
+        assert_eq!(poll(&mut future, &waker), Poll::Ready(Some(42)));
+        assert!(scheduler.finish(id));
+        // Only once
+        assert!(!scheduler.finish(id));
+    }
+
+    #[test]
+    fn cancel_the_request_when_the_map_moves() {
+        let waker = Arc::default();
+        let mut scheduler = Scheduler::new();
+        let (id, mut future) = scheduler.request(FakeFetch::default());
+        assert_eq!(poll(&mut future, &waker), Poll::Pending);
+
+        let cleared = Rc::new(Cell::new(false));
+        scheduler.moved(FakeTimer(Rc::clone(&cleared)));
+        // The task of the request is woken up to finish.
+        assert_eq!(waker.0.load(Ordering::Relaxed), 1);
+        assert_eq!(poll(&mut future, &waker), Poll::Ready(None));
+        assert!(!scheduler.finish(id));
+        assert!(!cleared.get());
+    }
+
+    #[test]
+    fn restart_the_debounce() {
+        let mut scheduler = Scheduler::new();
+        let first = Rc::new(Cell::new(false));
+        scheduler.moved(FakeTimer(Rc::clone(&first)));
+        let second = Rc::new(Cell::new(false));
+        scheduler.moved(FakeTimer(Rc::clone(&second)));
+        assert!(first.get());
+        assert!(!second.get());
+        scheduler.cancel();
+        assert!(second.get());
+    }
+
+    #[test]
+    fn ignore_the_result_of_a_previous_request() {
+        let waker = Arc::default();
+        let mut scheduler = Scheduler::<FakeTimer>::new();
+        let first_fetch = FakeFetch::default();
+        let first_response = Rc::clone(&first_fetch.response);
+        let (first_id, mut first) = scheduler.request(first_fetch);
+        first_response.set(Some(1));
+        // The result of the first request arrives after the second one has started.
+        assert_eq!(poll(&mut first, &waker), Poll::Ready(Some(1)));
+        let (second_id, mut second) = scheduler.request(FakeFetch::default());
+        assert_ne!(first_id, second_id);
+        assert!(!scheduler.finish(first_id));
+        assert_eq!(poll(&mut second, &waker), Poll::Pending);
+        assert!(scheduler.finish(second_id));
+    }
+
+    #[test]
+    fn cancel_everything() {
+        let waker = Arc::default();
+        let mut scheduler = Scheduler::new();
+        let (id, mut future) = scheduler.request(FakeFetch::default());
+        let cleared = Rc::new(Cell::new(false));
+        scheduler.moved(FakeTimer(Rc::clone(&cleared)));
+        scheduler.cancel();
+        assert!(cleared.get());
+        assert_eq!(poll(&mut future, &waker), Poll::Ready(None));
+        assert!(!scheduler.finish(id));
+    }
+}
@@ -0,0 +1,171 @@
+use std::{cell::Cell, rc::Rc};
+
+use web_sys::wasm_bindgen::{closure::Closure, JsCast as _, UnwrapThrowExt as _};
+
+use crate::{
+    map::is_loaded,
+    map::TrackedClosure,
+    projection::{self, lat_y, lng_x},
+    LatLngBounds,
+};
+
+/// A canvas in the overlay pane that covers the map container.
+pub(crate) struct CanvasOverlay {
+    map: leaflet::Map,
+    canvas: web_sys::HtmlCanvasElement,
+}
+
+/// The visible part of the map at the time the canvas has been reset.
+pub(crate) struct Viewport {
+    pub(crate) width: f64,
+    pub(crate) height: f64,
+    /// Size of the world in pixels.
+    pub(crate) scale: f64,
+    origin_x: f64,
+    origin_y: f64,
+}
+
+impl Viewport {
+    /// Convert web mercator coordinates in the range `[0, 1]` to container pixels.
+    pub(crate) fn to_pixels(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            x * self.scale - self.origin_x,
+            y * self.scale - self.origin_y,
+        )
+    }
+}
+
+pub(crate) fn create_canvas() -> web_sys::HtmlCanvasElement {
+    web_sys::window()
+        .unwrap_throw()
+        .document()
+        .unwrap_throw()
+        .create_element("canvas")
+        .unwrap_throw()
+        .unchecked_into()
+}
+
+impl CanvasOverlay {
+    pub(crate) fn new(map: &leaflet::Map) -> Self {
+        let canvas = create_canvas();
+        // Hide the canvas during zoom animations
+        // because it's not scaled accordingly.
+        canvas.set_class_name("leaflet-zoom-hide");
+        let style = canvas.style();
+        style.set_property("position", "absolute").unwrap_throw();
+        style.set_property("pointer-events", "none").unwrap_throw();
+        map.get_pane("overlayPane")
+            .append_child(&canvas)
+            .unwrap_throw();
+        Self {
+            map: map.clone(),
+            canvas,
+        }
+    }
+
+    /// Align the canvas with the map container and clear it.
+    ///
+    /// Returns `None` if the view of the map has not been set yet.
+    pub(crate) fn reset(
+        &self,
+        pixel_ratio: f64,
+    ) -> Option<(web_sys::CanvasRenderingContext2d, Viewport)> {
+        if !is_loaded(&self.map) {
+            return None;
+        }
+        let container = self.map.get_container();
+        let width = f64::from(container.client_width());
+        let height = f64::from(container.client_height());
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        {
+            // This also clears the canvas.
+            self.canvas.set_width((width * pixel_ratio) as u32);
+            self.canvas.set_height((height * pixel_ratio) as u32);
+        }
+        // The overlay pane is moved while panning,
+        // so the canvas has to be placed at the top left corner of the container.
+        let top_left = self
+            .map
+            .container_point_to_layer_point(&leaflet::Point::new(0.0, 0.0));
+        let style = self.canvas.style();
+        for (property, value) in [
+            ("width", format!("{width}px")),
+            ("height", format!("{height}px")),
+            (
+                "transform",
+                format!("translate({}px, {}px)", top_left.x(), top_left.y()),
+            ),
+        ] {
+            style.set_property(property, &value).unwrap_throw();
+        }
+        let ctx: web_sys::CanvasRenderingContext2d = self
+            .canvas
+            .get_context("2d")
+            .unwrap_throw()
+            .unwrap_throw()
+            .unchecked_into();
+        ctx.scale(pixel_ratio, pixel_ratio).unwrap_throw();
+        let bounds = LatLngBounds::from(self.map.get_bounds());
+        let scale = projection::scale(self.map.get_zoom());
+        let viewport = Viewport {
+            width,
+            height,
+            scale,
+            origin_x: lng_x(bounds.west()) * scale,
+            origin_y: lat_y(bounds.north()) * scale,
+        };
+        Some((ctx, viewport))
+    }
+
+    pub(crate) fn remove(&self) {
+        self.canvas.remove();
+    }
+}
+
+/// Calls a function at most once per animation frame,
+/// e.g. to redraw a canvas on `move` events.
+pub(crate) struct FrameThrottle {
+    /// The handle of the requested animation frame.
+    pending: Rc<Cell<Option<i32>>>,
+    callback: TrackedClosure<dyn Fn()>,
+}
+
+impl FrameThrottle {
+    pub(crate) fn new(f: impl Fn() + 'static) -> Self {
+        let pending = Rc::new(Cell::new(None));
+        let callback = {
+            let pending = Rc::clone(&pending);
+            Closure::<dyn Fn()>::new(move || {
+                pending.set(None);
+                f();
+            })
+        };
+        Self {
+            pending,
+            callback: TrackedClosure::new(callback),
+        }
+    }
+
+    /// Call the function on the next animation frame (unless it has already been requested).
+    pub(crate) fn request(&self) {
+        if self.pending.get().is_some() {
+            return;
+        }
+        let handle = web_sys::window()
+            .unwrap_throw()
+            .request_animation_frame(self.callback.as_ref().unchecked_ref())
+            .unwrap_throw();
+        self.pending.set(Some(handle));
+    }
+}
+
+impl Drop for FrameThrottle {
+    fn drop(&mut self) {
+        if let Some(handle) = self.pending.take() {
+            web_sys::window()
+                .unwrap_throw()
+                .cancel_animation_frame(handle)
+                .unwrap_throw();
+        }
+    }
+}
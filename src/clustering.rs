@@ -0,0 +1,471 @@
+//! Hierarchical greedy clustering of points.
+//!
+//! The algorithm is similar to the one of
+//! [supercluster](https://github.com/mapbox/supercluster):
+//! Starting at the maximum zoom level, each point is merged with all
+//! unclustered neighbors within a given pixel radius.
+//! The resulting clusters are then clustered again on the next lower zoom level.
+//!
+//! This module does not depend on Leaflet and can be used natively.
+
+use crate::{
+    projection::{lat_y, lng_x, wrap_lng, x_lng, y_lat, TILE_SIZE},
+    LatLng, LatLngBounds,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterOptions {
+    /// Cluster radius in pixels.
+    pub radius: f64,
+    /// Tile size in pixels, the `radius` is relative to.
+    pub tile_size: f64,
+    /// Minimum zoom level at which clusters are generated.
+    pub min_zoom: u8,
+    /// Maximum zoom level at which clusters are generated.
+    pub max_zoom: u8,
+    /// Minimum number of points to form a cluster.
+    pub min_points: usize,
+}
+
+impl Default for ClusterOptions {
+    fn default() -> Self {
+        Self {
+            radius: 40.0,
+            tile_size: TILE_SIZE,
+            min_zoom: 0,
+            max_zoom: 16,
+            min_points: 2,
+        }
+    }
+}
+
+/// Identifies a cluster (or a single point) at a specific zoom level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClusterId {
+    zoom: u8,
+    index: usize,
+}
+
+impl ClusterId {
+    #[must_use]
+    pub const fn zoom(&self) -> u8 {
+        self.zoom
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cluster {
+    pub id: ClusterId,
+    pub position: LatLng,
+    /// Number of points in this cluster.
+    pub count: usize,
+    /// Index of the original point if the cluster consists of a single point.
+    pub point: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    /// Web mercator x coordinate in the range `[0, 1]`.
+    x: f64,
+    /// Web mercator y coordinate in the range `[0, 1]`.
+    y: f64,
+    count: usize,
+    point: Option<usize>,
+    /// Index of the cluster at the next lower zoom level.
+    parent: Option<usize>,
+    /// Indices of the nodes at the next higher zoom level that form this node.
+    children: Vec<usize>,
+}
+
+/// Clusters of points for all zoom levels.
+#[derive(Debug, Clone)]
+pub struct ClusterIndex {
+    options: ClusterOptions,
+    /// The nodes of each zoom level from `0` to `max_zoom + 1`.
+    ///
+    /// The level `max_zoom + 1` contains the original points.
+    levels: Vec<Vec<Node>>,
+}
+
+impl ClusterIndex {
+    #[must_use]
+    pub fn new(points: &[LatLng], options: ClusterOptions) -> Self {
+        debug_assert!(options.min_zoom <= options.max_zoom);
+        let leaves = points
+            .iter()
+            .enumerate()
+            .map(|(idx, point)| Node {
+                // Points of copies of the world (e.g. `lng = 190.0`) are clustered
+                // with the ones of the original world.
+                x: lng_x(wrap_lng(point.lng)),
+                y: lat_y(point.lat),
+                count: 1,
+                point: Some(idx),
+                parent: None,
+                children: Vec::new(),
+            })
+            .collect();
+        let max_zoom = usize::from(options.max_zoom);
+        let mut levels = vec![Vec::new(); max_zoom + 2];
+        levels[max_zoom + 1] = leaves;
+        for zoom in (options.min_zoom..=options.max_zoom).rev() {
+            let (coarser, finer) = levels.split_at_mut(usize::from(zoom) + 1);
+            coarser[usize::from(zoom)] = cluster(&mut finer[0], zoom, &options);
+        }
+        Self { options, levels }
+    }
+
+    #[must_use]
+    pub const fn options(&self) -> &ClusterOptions {
+        &self.options
+    }
+
+    /// Get the clusters within the bounds at the given zoom level.
+    #[must_use]
+    pub fn clusters(&self, bounds: &LatLngBounds, zoom: f64) -> Vec<Cluster> {
+        let zoom = self.level(zoom);
+        let (west, east) = (bounds.west(), bounds.east());
+        let min_y = lat_y(bounds.north());
+        let max_y = lat_y(bounds.south());
+        let x_ranges = if east - west >= 360.0 {
+            [(0.0, 1.0), (1.0, 0.0)]
+        } else {
+            let min_x = lng_x(wrap_lng(west));
+            let max_x = lng_x(wrap_lng(east));
+            if min_x <= max_x {
+                [(min_x, max_x), (1.0, 0.0)]
+            } else {
+                // The bounds cross the antimeridian.
+                [(min_x, 1.0), (0.0, max_x)]
+            }
+        };
+        self.levels[usize::from(zoom)]
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| {
+                node.y >= min_y
+                    && node.y <= max_y
+                    && x_ranges
+                        .iter()
+                        .any(|(min_x, max_x)| node.x >= *min_x && node.x <= *max_x)
+            })
+            .map(|(index, node)| node.to_cluster(ClusterId { zoom, index }))
+            .collect()
+    }
+
+    /// Get the clusters (or points) of the next higher zoom level
+    /// that form the given cluster.
+    #[must_use]
+    pub fn children(&self, id: ClusterId) -> Vec<Cluster> {
+        let zoom = id.zoom + 1;
+        let (Some(node), Some(level)) = (self.node(id), self.levels.get(usize::from(zoom))) else {
+            return Vec::new();
+        };
+        node.children
+            .iter()
+            .map(|&index| level[index].to_cluster(ClusterId { zoom, index }))
+            .collect()
+    }
+
+    /// Get the zoom level at which the cluster expands into multiple children.
+    #[must_use]
+    pub fn expansion_zoom(&self, id: ClusterId) -> u8 {
+        let mut id = id;
+        while let Some(node) = self.node(id) {
+            match node.children[..] {
+                [index] => {
+                    id = ClusterId {
+                        zoom: id.zoom + 1,
+                        index,
+                    };
+                }
+                [] => break,
+                _ => return id.zoom + 1,
+            }
+        }
+        id.zoom
+    }
+
+    fn node(&self, id: ClusterId) -> Option<&Node> {
+        self.levels.get(usize::from(id.zoom))?.get(id.index)
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn level(&self, zoom: f64) -> u8 {
+        let min = f64::from(self.options.min_zoom);
+        let max = f64::from(self.options.max_zoom) + 1.0;
+        zoom.floor().clamp(min, max) as u8
+    }
+}
+
+impl Node {
+    fn to_cluster(&self, id: ClusterId) -> Cluster {
+        Cluster {
+            id,
+            position: LatLng::new(y_lat(self.y), x_lng(self.x)),
+            count: self.count,
+            point: self.point,
+        }
+    }
+}
+
+/// Cluster the nodes of the next higher zoom level.
+#[allow(clippy::cast_precision_loss)]
+fn cluster(nodes: &mut [Node], zoom: u8, options: &ClusterOptions) -> Vec<Node> {
+    let radius = options.radius / (options.tile_size * 2_f64.powi(i32::from(zoom)));
+    let grid = Grid::new(nodes, radius);
+    let mut clusters = Vec::new();
+    let mut neighbors = Vec::new();
+    for idx in 0..nodes.len() {
+        if nodes[idx].parent.is_some() {
+            continue;
+        }
+        let parent = clusters.len();
+        let node = &nodes[idx];
+        let (node_x, node_y, node_count, node_point) = (node.x, node.y, node.count, node.point);
+        neighbors.clear();
+        neighbors.extend(
+            grid.neighbors(nodes, node_x, node_y, radius)
+                .filter(|&n| n != idx && nodes[n].parent.is_none()),
+        );
+        let count = node_count + neighbors.iter().map(|&n| nodes[n].count).sum::<usize>();
+        nodes[idx].parent = Some(parent);
+        if neighbors.is_empty() || count < options.min_points {
+            clusters.push(Node {
+                x: node_x,
+                y: node_y,
+                count: node_count,
+                point: node_point,
+                parent: None,
+                children: vec![idx],
+            });
+            continue;
+        }
+        let mut x = node_x * node_count as f64;
+        let mut y = node_y * node_count as f64;
+        let mut children = Vec::with_capacity(neighbors.len() + 1);
+        children.push(idx);
+        for &n in &neighbors {
+            let neighbor = &mut nodes[n];
+            x += neighbor.x * neighbor.count as f64;
+            y += neighbor.y * neighbor.count as f64;
+            neighbor.parent = Some(parent);
+            children.push(n);
+        }
+        clusters.push(Node {
+            x: x / count as f64,
+            y: y / count as f64,
+            count,
+            point: None,
+            parent: None,
+            children,
+        });
+    }
+    clusters
+}
+
+/// A uniform grid to look up the neighbors of a node.
+///
+/// The nodes are sorted by their cell (column by column)
+/// so that the nodes of adjacent cells within a column are contiguous.
+struct Grid {
+    cell_size: f64,
+    cells: Vec<((i64, i64), usize)>,
+}
+
+impl Grid {
+    fn new(nodes: &[Node], cell_size: f64) -> Self {
+        let mut cells: Vec<_> = nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (cell(node.x, node.y, cell_size), idx))
+            .collect();
+        cells.sort_unstable();
+        Self { cell_size, cells }
+    }
+
+    fn neighbors<'a>(
+        &'a self,
+        nodes: &'a [Node],
+        x: f64,
+        y: f64,
+        radius: f64,
+    ) -> impl Iterator<Item = usize> + 'a {
+        let (cx, cy) = cell(x, y, self.cell_size);
+        (cx - 1..=cx + 1)
+            .flat_map(move |cx| {
+                let start = self.cells.partition_point(|(key, _)| *key < (cx, cy - 1));
+                self.cells[start..]
+                    .iter()
+                    .take_while(move |(key, _)| *key <= (cx, cy + 1))
+            })
+            .map(|(_, idx)| *idx)
+            .filter(move |&n| {
+                let dx = nodes[n].x - x;
+                let dy = nodes[n].y - y;
+                dx * dx + dy * dy <= radius * radius
+            })
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn cell(x: f64, y: f64, cell_size: f64) -> (i64, i64) {
+    (
+        (x / cell_size).floor() as i64,
+        (y / cell_size).floor() as i64,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn world() -> LatLngBounds {
+        LatLngBounds::new(LatLng::new(-85.0, -180.0), LatLng::new(85.0, 180.0))
+    }
+
+    /// Two points that are separated at zoom level 10 and a distant one.
+    fn points() -> Vec<LatLng> {
+        vec![
+            LatLng::new(0.0, 0.0),
+            LatLng::new(0.0, 0.1),
+            LatLng::new(40.0, 100.0),
+        ]
+    }
+
+    fn sorted_by_count(mut clusters: Vec<Cluster>) -> Vec<Cluster> {
+        clusters.sort_by_key(|cluster| cluster.count);
+        clusters
+    }
+
+    #[test]
+    fn original_points_above_max_zoom() {
+        let index = ClusterIndex::new(&points(), ClusterOptions::default());
+        for zoom in [17.0, 20.0] {
+            let clusters = index.clusters(&world(), zoom);
+            assert_eq!(clusters.len(), 3);
+            assert!(clusters.iter().all(|cluster| cluster.count == 1));
+            let mut points: Vec<_> = clusters.iter().filter_map(|c| c.point).collect();
+            points.sort_unstable();
+            assert_eq!(points, vec![0, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn cluster_counts_per_level() {
+        let index = ClusterIndex::new(&points(), ClusterOptions::default());
+        for zoom in 0..=9 {
+            let clusters = sorted_by_count(index.clusters(&world(), f64::from(zoom)));
+            assert_eq!(clusters.len(), 2, "zoom {zoom}");
+            assert_eq!(clusters[0].point, Some(2));
+            assert_eq!(clusters[1].count, 2);
+            assert_eq!(clusters[1].point, None);
+        }
+        for zoom in 10..=16 {
+            assert_eq!(index.clusters(&world(), f64::from(zoom)).len(), 3);
+        }
+    }
+
+    #[test]
+    fn cluster_centroid() {
+        let index = ClusterIndex::new(&points(), ClusterOptions::default());
+        let clusters = sorted_by_count(index.clusters(&world(), 0.0));
+        let position = clusters[1].position;
+        assert!(position.lat.abs() < EPSILON);
+        assert!((position.lng - 0.05).abs() < EPSILON);
+        let position = clusters[0].position;
+        assert!((position.lat - 40.0).abs() < EPSILON);
+        assert!((position.lng - 100.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn min_points() {
+        let options = ClusterOptions {
+            min_points: 3,
+            ..ClusterOptions::default()
+        };
+        let index = ClusterIndex::new(&points(), options);
+        assert_eq!(index.clusters(&world(), 0.0).len(), 3);
+    }
+
+    #[test]
+    fn children() {
+        let index = ClusterIndex::new(&points(), ClusterOptions::default());
+        let cluster = sorted_by_count(index.clusters(&world(), 0.0))[1];
+        let children = index.children(cluster.id);
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].id.zoom(), 1);
+        assert_eq!(children[0].count, 2);
+
+        let cluster = sorted_by_count(index.clusters(&world(), 9.0))[1];
+        let mut children: Vec<_> = index
+            .children(cluster.id)
+            .iter()
+            .map(|child| (child.id.zoom(), child.count, child.point))
+            .collect();
+        children.sort_by_key(|(_, _, point)| *point);
+        assert_eq!(children, vec![(10, 1, Some(0)), (10, 1, Some(1))]);
+
+        let point = index.clusters(&world(), 17.0)[0];
+        assert!(index.children(point.id).is_empty());
+    }
+
+    #[test]
+    fn expansion_zoom() {
+        let index = ClusterIndex::new(&points(), ClusterOptions::default());
+        let clusters = sorted_by_count(index.clusters(&world(), 0.0));
+        assert_eq!(index.expansion_zoom(clusters[1].id), 10);
+        // A single point never expands.
+        assert_eq!(index.expansion_zoom(clusters[0].id), 17);
+    }
+
+    #[test]
+    fn bounds_across_the_antimeridian() {
+        let points = [
+            LatLng::new(0.0, 179.9),
+            LatLng::new(0.0, -179.9),
+            LatLng::new(0.0, 0.0),
+        ];
+        let index = ClusterIndex::new(&points, ClusterOptions::default());
+        let bounds = LatLngBounds::new(LatLng::new(-10.0, 170.0), LatLng::new(10.0, 190.0));
+        let mut found: Vec<_> = index
+            .clusters(&bounds, 17.0)
+            .iter()
+            .filter_map(|cluster| cluster.point)
+            .collect();
+        found.sort_unstable();
+        assert_eq!(found, vec![0, 1]);
+    }
+
+    #[test]
+    fn normalize_longitudes_outside_of_the_world() {
+        let points = [
+            LatLng::new(0.0, 0.0),
+            LatLng::new(0.0, 360.0),
+            LatLng::new(0.0, -170.0),
+            LatLng::new(0.0, 190.0),
+        ];
+        let index = ClusterIndex::new(&points, ClusterOptions::default());
+        let mut counts: Vec<_> = index
+            .clusters(&world(), 16.0)
+            .iter()
+            .map(|cluster| cluster.count)
+            .collect();
+        counts.sort_unstable();
+        assert_eq!(counts, vec![2, 2]);
+
+        let bounds = LatLngBounds::new(LatLng::new(-10.0, -175.0), LatLng::new(10.0, -165.0));
+        let mut found: Vec<_> = index
+            .clusters(&bounds, 17.0)
+            .iter()
+            .filter_map(|cluster| cluster.point)
+            .collect();
+        found.sort_unstable();
+        assert_eq!(found, vec![2, 3]);
+        for cluster in index.clusters(&bounds, 17.0) {
+            assert!((cluster.position.lng + 170.0).abs() < EPSILON);
+        }
+    }
+}
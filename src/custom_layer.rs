@@ -0,0 +1,81 @@
+use web_sys::wasm_bindgen::JsCast;
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker},
+    DynMessage,
+};
+
+use crate::{MapChildElement, MapCtx};
+
+/// A Leaflet layer type that is not covered by the views of this crate,
+/// e.g. the layer of a Leaflet plugin.
+///
+/// Implement it for the parameters of the layer
+/// and add them to the map with [`custom_layer`].
+/// The view adds the created layer to the map and removes it on teardown.
+///
+/// Views that need more control (e.g. to send messages)
+/// can implement `View<State, Action, MapCtx, DynMessage>` themselves
+/// with [`MapChildElement::Layer`] as element.
+pub trait MapLayer: 'static {
+    /// The (`wasm_bindgen`) type of the Leaflet layer.
+    type Layer: JsCast;
+
+    /// Create the layer (without adding it to the map).
+    fn create(&self, map: &leaflet::Map) -> Self::Layer;
+
+    /// Apply the changes since the previous view to the layer.
+    fn update(&self, prev: &Self, layer: &Self::Layer);
+}
+
+/// Add a layer of a custom type to the map (see [`MapLayer`]).
+pub const fn custom_layer<L: MapLayer>(layer: L) -> CustomLayer<L> {
+    CustomLayer { layer }
+}
+
+pub struct CustomLayer<L> {
+    layer: L,
+}
+
+impl<L> ViewMarker for CustomLayer<L> {}
+
+impl<State, Action, L: MapLayer> View<State, Action, MapCtx, DynMessage> for CustomLayer<L> {
+    type Element = MapChildElement;
+
+    type ViewState = ();
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        let layer: leaflet::Layer = self.layer.create(ctx.map()).unchecked_into();
+        layer.add_to(ctx.map());
+        (MapChildElement::Layer(layer), ())
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        _: &mut Self::ViewState,
+        _: &mut MapCtx,
+        element: Mut<Self::Element>,
+    ) {
+        match element.as_layer_mut() {
+            Some(layer) => self.layer.update(&prev.layer, layer.unchecked_ref()),
+            None => log::error!("The element of a custom layer is not a layer: {element:?}"),
+        }
+    }
+
+    fn teardown(&self, _: &mut Self::ViewState, _: &mut MapCtx, element: Mut<Self::Element>) {
+        match element.as_layer_mut() {
+            Some(layer) => layer.remove(),
+            None => log::error!("The element of a custom layer is not a layer: {element:?}"),
+        }
+    }
+
+    fn message(
+        &self,
+        _: &mut Self::ViewState,
+        _: &[ViewId],
+        message: DynMessage,
+        _: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        MessageResult::Stale(message)
+    }
+}
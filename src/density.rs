@@ -0,0 +1,315 @@
+//! Kernel density estimation on a raster.
+//!
+//! This module does not depend on Leaflet and can be used natively.
+
+/// The default gradient (from blue over cyan, lime and yellow to red).
+const DEFAULT_GRADIENT: [(f64, [u8; 3]); 5] = [
+    (0.4, [0, 0, 255]),
+    (0.6, [0, 255, 255]),
+    (0.7, [0, 255, 0]),
+    (0.8, [255, 255, 0]),
+    (1.0, [255, 0, 0]),
+];
+
+/// The contribution of a single point depending on the distance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityKernel {
+    /// Radius in pixels beyond which a point has no influence.
+    pub radius: f64,
+    /// Part of the radius (from `0.0` to `1.0`) over which the influence fades out.
+    pub blur: f64,
+}
+
+impl Default for DensityKernel {
+    fn default() -> Self {
+        Self {
+            radius: 25.0,
+            blur: 0.6,
+        }
+    }
+}
+
+impl DensityKernel {
+    /// The influence (from `0.0` to `1.0`) of a point at the given distance.
+    #[must_use]
+    pub fn influence(&self, distance: f64) -> f64 {
+        let blur = self.blur.clamp(0.0, 1.0);
+        let core = self.radius * (1.0 - blur);
+        if distance <= core {
+            return 1.0;
+        }
+        if distance >= self.radius {
+            return 0.0;
+        }
+        // Smoothstep between the core and the radius.
+        let t = (self.radius - distance) / (self.radius - core);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    /// Precompute the influence for all pixel offsets within the radius.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    fn stamp(&self) -> Stamp {
+        let extent = self.radius.max(0.0).ceil() as i64;
+        let size = 2 * extent + 1;
+        let values = (-extent..=extent)
+            .flat_map(|dy| (-extent..=extent).map(move |dx| (dx, dy)))
+            .map(|(dx, dy)| self.influence(((dx * dx + dy * dy) as f64).sqrt()))
+            .collect();
+        Stamp {
+            extent,
+            size,
+            values,
+        }
+    }
+}
+
+struct Stamp {
+    extent: i64,
+    size: i64,
+    values: Vec<f64>,
+}
+
+/// Colors for the normalized density from `0.0` to `1.0`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    stops: Vec<(f64, [u8; 3])>,
+}
+
+impl Gradient {
+    /// Create a gradient from color stops (position from `0.0` to `1.0` and RGB color).
+    #[must_use]
+    pub fn new(stops: impl IntoIterator<Item = (f64, [u8; 3])>) -> Self {
+        let mut stops: Vec<_> = stops.into_iter().collect();
+        stops.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        Self { stops }
+    }
+
+    /// The (linearly interpolated) color at the given position.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn color(&self, position: f64) -> [u8; 3] {
+        let Some(&(first_position, first_color)) = self.stops.first() else {
+            return [0, 0, 0];
+        };
+        if position <= first_position {
+            return first_color;
+        }
+        for window in self.stops.windows(2) {
+            let (start, start_color) = window[0];
+            let (end, end_color) = window[1];
+            if position <= end {
+                let t = if end > start {
+                    (position - start) / (end - start)
+                } else {
+                    1.0
+                };
+                return std::array::from_fn(|i| {
+                    let start = f64::from(start_color[i]);
+                    let end = f64::from(end_color[i]);
+                    (start + (end - start) * t).round() as u8
+                });
+            }
+        }
+        self.stops.last().map_or([0, 0, 0], |(_, color)| *color)
+    }
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self::new(DEFAULT_GRADIENT)
+    }
+}
+
+/// Accumulated density values of a raster.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityRaster {
+    width: usize,
+    height: usize,
+    values: Vec<f64>,
+}
+
+impl DensityRaster {
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            values: vec![0.0; width * height],
+        }
+    }
+
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The density values row by row.
+    #[must_use]
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// The maximum density value.
+    #[must_use]
+    pub fn max(&self) -> f64 {
+        self.values.iter().copied().fold(0.0, f64::max)
+    }
+
+    /// Add the weighted influence of points given as `(x, y, weight)` in pixels.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss
+    )]
+    pub fn accumulate(
+        &mut self,
+        points: impl IntoIterator<Item = (f64, f64, f64)>,
+        kernel: &DensityKernel,
+    ) {
+        let stamp = kernel.stamp();
+        let width = self.width as i64;
+        let height = self.height as i64;
+        for (x, y, weight) in points {
+            let cx = x.round() as i64;
+            let cy = y.round() as i64;
+            let min_x = (cx - stamp.extent).max(0);
+            let max_x = (cx + stamp.extent).min(width - 1);
+            let min_y = (cy - stamp.extent).max(0);
+            let max_y = (cy + stamp.extent).min(height - 1);
+            for py in min_y..=max_y {
+                let stamp_row = (py - cy + stamp.extent) * stamp.size;
+                let row = py * width;
+                for px in min_x..=max_x {
+                    let influence = stamp.values[(stamp_row + px - cx + stamp.extent) as usize];
+                    self.values[(row + px) as usize] += influence * weight;
+                }
+            }
+        }
+    }
+
+    /// Colorize the density as RGBA pixels.
+    ///
+    /// The density is normalized by `max` and the opacity
+    /// increases with the density, starting at `min_opacity`.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn to_rgba(&self, gradient: &Gradient, max: f64, min_opacity: f64) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity(self.values.len() * 4);
+        for &value in &self.values {
+            if value <= 0.0 || max <= 0.0 {
+                rgba.extend_from_slice(&[0, 0, 0, 0]);
+                continue;
+            }
+            let position = (value / max).min(1.0);
+            let [r, g, b] = gradient.color(position);
+            let alpha = (position.max(min_opacity) * 255.0).round() as u8;
+            rgba.extend_from_slice(&[r, g, b, alpha]);
+        }
+        rgba
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    #[test]
+    fn kernel_influence() {
+        let kernel = DensityKernel {
+            radius: 10.0,
+            blur: 0.5,
+        };
+        assert!((kernel.influence(0.0) - 1.0).abs() < EPSILON);
+        assert!((kernel.influence(5.0) - 1.0).abs() < EPSILON);
+        assert!((kernel.influence(7.5) - 0.5).abs() < EPSILON);
+        assert!(kernel.influence(10.0).abs() < EPSILON);
+        assert!(kernel.influence(20.0).abs() < EPSILON);
+        // Decreasing between the core and the radius.
+        assert!(kernel.influence(6.0) > kernel.influence(7.0));
+        assert!(kernel.influence(8.0) > kernel.influence(9.0));
+    }
+
+    #[test]
+    fn kernel_without_blur() {
+        let kernel = DensityKernel {
+            radius: 10.0,
+            blur: 0.0,
+        };
+        assert!((kernel.influence(10.0) - 1.0).abs() < EPSILON);
+        assert!(kernel.influence(10.1).abs() < EPSILON);
+    }
+
+    #[test]
+    fn accumulate_points() {
+        let kernel = DensityKernel {
+            radius: 1.0,
+            blur: 0.0,
+        };
+        let mut raster = DensityRaster::new(4, 3);
+        raster.accumulate([(1.0, 1.0, 1.0), (2.0, 1.0, 2.0)], &kernel);
+        #[rustfmt::skip]
+        let expected = [
+            0.0, 1.0, 2.0, 0.0,
+            1.0, 3.0, 3.0, 2.0,
+            0.0, 1.0, 2.0, 0.0,
+        ];
+        assert_eq!(raster.values(), expected);
+        assert!((raster.max() - 3.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn accumulate_points_at_the_border() {
+        let kernel = DensityKernel {
+            radius: 2.5,
+            blur: 0.0,
+        };
+        let mut raster = DensityRaster::new(2, 2);
+        raster.accumulate([(-1.0, -1.0, 1.0), (10.0, 10.0, 1.0)], &kernel);
+        // Only the pixels within the radius of the first point.
+        assert_eq!(raster.values(), [1.0, 1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn normalize_to_rgba() {
+        let kernel = DensityKernel {
+            radius: 0.0,
+            blur: 0.0,
+        };
+        let mut raster = DensityRaster::new(3, 1);
+        raster.accumulate([(1.0, 0.0, 1.0), (2.0, 0.0, 4.0)], &kernel);
+        let gradient = Gradient::new([(0.0, [0, 0, 0]), (1.0, [200, 100, 0])]);
+        let rgba = raster.to_rgba(&gradient, 2.0, 0.1);
+        assert_eq!(
+            rgba,
+            [
+                0, 0, 0, 0, // no density
+                100, 50, 0, 128, // half of the maximum
+                200, 100, 0, 255, // clamped to the maximum
+            ]
+        );
+        // The minimum opacity
+        assert_eq!(raster.to_rgba(&gradient, 100.0, 0.1)[7], 26);
+        // No maximum
+        assert!(raster.to_rgba(&gradient, 0.0, 0.1).iter().all(|&c| c == 0));
+    }
+
+    #[test]
+    fn gradient_lookup() {
+        let gradient = Gradient::new([(1.0, [255, 0, 0]), (0.5, [0, 0, 255])]);
+        assert_eq!(gradient.color(0.0), [0, 0, 255]);
+        assert_eq!(gradient.color(0.5), [0, 0, 255]);
+        assert_eq!(gradient.color(0.75), [128, 0, 128]);
+        assert_eq!(gradient.color(1.0), [255, 0, 0]);
+        assert_eq!(gradient.color(2.0), [255, 0, 0]);
+        assert_eq!(Gradient::new([]).color(0.5), [0, 0, 0]);
+        assert_eq!(Gradient::default().color(0.0), [0, 0, 255]);
+        assert_eq!(Gradient::default().color(0.7), [0, 255, 0]);
+    }
+}
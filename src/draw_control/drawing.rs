@@ -0,0 +1,266 @@
+//! Drawing new shapes with the [`draw_control`](super::draw_control),
+//! independent of the map and the browser.
+
+use crate::{measure::haversine_distance, LatLng, LatLngBounds, Shape};
+
+/// What a click on the map does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawTool {
+    /// Click to place a marker.
+    Marker,
+    /// Click to add vertices and double click to finish.
+    Polyline,
+    /// Click to add vertices and double click to finish.
+    Polygon,
+    /// Drag from one corner to the opposite one.
+    Rectangle,
+    /// Drag from the center to the circumference.
+    Circle,
+    /// Click on a shape to show its handles and drag them.
+    Edit,
+    /// Click on a shape to delete it.
+    Delete,
+}
+
+/// What the map has to do after an event.
+#[derive(Debug, PartialEq)]
+pub(super) enum DrawStep {
+    Ignore,
+    /// A click beside the shapes deselects the edited shape.
+    Deselect,
+    /// Show the preview of the shape that is drawn (empty if the drawing has been cancelled).
+    Sketch(Vec<LatLng>),
+    /// The shape has been drawn and the preview must be hidden.
+    Created(Shape),
+}
+
+/// The active tool and the shape that is drawn with it.
+pub(super) struct Drawing {
+    tool: Option<DrawTool>,
+    /// Vertices of the polyline or polygon that is drawn.
+    vertices: Vec<LatLng>,
+    /// Start of the rectangle or circle that is drawn.
+    drag_start: Option<LatLng>,
+}
+
+impl Drawing {
+    pub(super) const fn new() -> Self {
+        Self {
+            tool: None,
+            vertices: Vec::new(),
+            drag_start: None,
+        }
+    }
+
+    pub(super) const fn tool(&self) -> Option<DrawTool> {
+        self.tool
+    }
+
+    /// Change the tool, which cancels the drawing.
+    pub(super) fn set_tool(&mut self, tool: Option<DrawTool>) {
+        self.cancel();
+        self.tool = tool;
+    }
+
+    pub(super) fn cancel(&mut self) {
+        self.vertices.clear();
+        self.drag_start = None;
+    }
+
+    /// Handle a mouse event (e.g. `click`) at the given position.
+    pub(super) fn handle_event(&mut self, event_type: &str, position: LatLng) -> DrawStep {
+        let Some(tool) = self.tool else {
+            return DrawStep::Ignore;
+        };
+        match (tool, event_type) {
+            (DrawTool::Marker, "click") => DrawStep::Created(Shape::Marker(position)),
+            // A click beside the shapes (see `DrawLayer::create_shape`)
+            (DrawTool::Edit, "click") => DrawStep::Deselect,
+            (DrawTool::Polyline | DrawTool::Polygon, "click") => {
+                self.vertices.push(position);
+                DrawStep::Sketch(self.vertices.clone())
+            }
+            (DrawTool::Polyline | DrawTool::Polygon, "mousemove") => {
+                if self.vertices.is_empty() {
+                    return DrawStep::Ignore;
+                }
+                let mut points = self.vertices.clone();
+                points.push(position);
+                DrawStep::Sketch(points)
+            }
+            (DrawTool::Polyline | DrawTool::Polygon, "dblclick") => {
+                // Both clicks of the double click have added a vertex.
+                self.vertices.pop();
+                let vertices = std::mem::take(&mut self.vertices);
+                match tool {
+                    DrawTool::Polyline if vertices.len() > 1 => {
+                        DrawStep::Created(Shape::Polyline(vertices))
+                    }
+                    DrawTool::Polygon if vertices.len() > 2 => {
+                        DrawStep::Created(Shape::Polygon(vertices))
+                    }
+                    _ => DrawStep::Sketch(Vec::new()),
+                }
+            }
+            (DrawTool::Rectangle | DrawTool::Circle, "mousedown") => {
+                self.drag_start = Some(position);
+                DrawStep::Ignore
+            }
+            (DrawTool::Rectangle | DrawTool::Circle, "mousemove") => match self.drag_start {
+                Some(start) => DrawStep::Sketch(area_shape(tool, start, position).outline()),
+                None => DrawStep::Ignore,
+            },
+            (DrawTool::Rectangle | DrawTool::Circle, "mouseup") => match self.drag_start.take() {
+                // A click without dragging does not create an empty shape.
+                Some(start) if start != position => {
+                    DrawStep::Created(area_shape(tool, start, position))
+                }
+                Some(_) => DrawStep::Sketch(Vec::new()),
+                None => DrawStep::Ignore,
+            },
+            _ => DrawStep::Ignore,
+        }
+    }
+}
+
+fn area_shape(tool: DrawTool, start: LatLng, end: LatLng) -> Shape {
+    if tool == DrawTool::Circle {
+        Shape::Circle {
+            center: start,
+            radius: haversine_distance(start, end),
+        }
+    } else {
+        Shape::Rectangle(LatLngBounds::new(start, end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: LatLng = LatLng::new(0.0, 0.0);
+    const B: LatLng = LatLng::new(1.0, 1.0);
+    const C: LatLng = LatLng::new(0.0, 2.0);
+
+    fn drawing(tool: DrawTool) -> Drawing {
+        let mut drawing = Drawing::new();
+        drawing.set_tool(Some(tool));
+        drawing
+    }
+
+    /// Click the points and double click the last one.
+    fn draw_vertices(drawing: &mut Drawing, points: &[LatLng]) -> DrawStep {
+        for &point in points {
+            drawing.handle_event("click", point);
+        }
+        let last = *points.last().unwrap();
+        drawing.handle_event("click", last);
+        drawing.handle_event("dblclick", last)
+    }
+
+    #[test]
+    fn ignore_events_without_tool() {
+        let mut drawing = Drawing::new();
+        assert_eq!(drawing.handle_event("click", A), DrawStep::Ignore);
+    }
+
+    #[test]
+    fn place_a_marker() {
+        let mut drawing = drawing(DrawTool::Marker);
+        assert_eq!(
+            drawing.handle_event("click", A),
+            DrawStep::Created(Shape::Marker(A))
+        );
+    }
+
+    #[test]
+    fn double_click_finishes_a_polyline_without_a_duplicate_vertex() {
+        let mut drawing = drawing(DrawTool::Polyline);
+        assert_eq!(drawing.handle_event("click", A), DrawStep::Sketch(vec![A]));
+        assert_eq!(
+            drawing.handle_event("mousemove", B),
+            DrawStep::Sketch(vec![A, B])
+        );
+        assert_eq!(
+            draw_vertices(&mut drawing, &[B]),
+            DrawStep::Created(Shape::Polyline(vec![A, B]))
+        );
+        // The next polyline starts without vertices.
+        assert_eq!(drawing.handle_event("mousemove", B), DrawStep::Ignore);
+    }
+
+    #[test]
+    fn polygon_needs_three_vertices() {
+        let mut drawing = drawing(DrawTool::Polygon);
+        assert_eq!(
+            draw_vertices(&mut drawing, &[A, B]),
+            DrawStep::Sketch(Vec::new())
+        );
+        assert_eq!(
+            draw_vertices(&mut drawing, &[A, B, C]),
+            DrawStep::Created(Shape::Polygon(vec![A, B, C]))
+        );
+    }
+
+    #[test]
+    fn polyline_needs_two_vertices() {
+        let mut drawing = drawing(DrawTool::Polyline);
+        assert_eq!(
+            draw_vertices(&mut drawing, &[A]),
+            DrawStep::Sketch(Vec::new())
+        );
+    }
+
+    #[test]
+    fn drag_a_rectangle() {
+        let mut drawing = drawing(DrawTool::Rectangle);
+        assert_eq!(drawing.handle_event("mousemove", A), DrawStep::Ignore);
+        drawing.handle_event("mousedown", A);
+        assert_eq!(
+            drawing.handle_event("mousemove", B),
+            DrawStep::Sketch(Shape::Rectangle(LatLngBounds::new(A, B)).outline())
+        );
+        assert_eq!(
+            drawing.handle_event("mouseup", B),
+            DrawStep::Created(Shape::Rectangle(LatLngBounds::new(A, B)))
+        );
+        assert_eq!(drawing.handle_event("mouseup", B), DrawStep::Ignore);
+    }
+
+    #[test]
+    fn ignore_zero_size_rectangles() {
+        let mut drawing = drawing(DrawTool::Rectangle);
+        drawing.handle_event("mousedown", A);
+        assert_eq!(
+            drawing.handle_event("mouseup", A),
+            DrawStep::Sketch(Vec::new())
+        );
+    }
+
+    #[test]
+    fn drag_a_circle() {
+        let mut drawing = drawing(DrawTool::Circle);
+        drawing.handle_event("mousedown", A);
+        let DrawStep::Created(Shape::Circle { center, radius }) =
+            drawing.handle_event("mouseup", B)
+        else {
+            panic!("no circle");
+        };
+        assert_eq!(center, A);
+        assert!((radius - haversine_distance(A, B)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn changing_the_tool_cancels_the_drawing() {
+        let mut drawing = drawing(DrawTool::Polyline);
+        drawing.handle_event("click", A);
+        drawing.set_tool(Some(DrawTool::Polygon));
+        assert_eq!(drawing.handle_event("mousemove", B), DrawStep::Ignore);
+    }
+
+    #[test]
+    fn edit_tool_deselects_on_map_click() {
+        let mut drawing = drawing(DrawTool::Edit);
+        assert_eq!(drawing.handle_event("click", A), DrawStep::Deselect);
+    }
+}
@@ -0,0 +1,480 @@
+mod drawing;
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    marker::PhantomData,
+    rc::{Rc, Weak},
+};
+
+use web_sys::{
+    js_sys::Array,
+    wasm_bindgen::{JsCast as _, UnwrapThrowExt as _},
+};
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker as _},
+    DynMessage, MessageThunk,
+};
+
+use self::drawing::{DrawStep, Drawing};
+use crate::{
+    geometry::lat_lng_array,
+    map::{handler_enabled, set_handler_enabled, stop_propagation, EventListener},
+    LatLng, MapChildElement, MapCtx, Shape,
+};
+
+pub use self::drawing::DrawTool;
+
+/// CSS class of the handles that are shown while editing a shape.
+pub const DRAW_HANDLE_CLASS_NAME: &str = "draw-handle";
+
+/// Handle icon size in pixels.
+const HANDLE_SIZE: f64 = 10.0;
+
+/// Distinctive ID for better debugging
+const DRAW_CONTROL_ID: ViewId = ViewId::new(23672);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DrawOptions {
+    /// The active tool (`None` disables drawing and editing).
+    pub tool: Option<DrawTool>,
+}
+
+/// A change of the shapes that has been made by the user.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawEvent<K> {
+    Created(Shape),
+    Edited(K, Shape),
+    Deleted(K),
+}
+
+/// Draw, edit and delete shapes.
+///
+/// The shapes are owned by the app state: A [`DrawEvent`] only reports
+/// the change and the shapes on the map are updated
+/// when the view is rebuilt with the changed shapes.
+/// Ignoring (or reverting) a change therefore restores the previous shapes.
+pub fn draw_control<State, K, F>(
+    shapes: Vec<(K, Shape)>,
+    options: DrawOptions,
+    on_event: F,
+) -> DrawControl<State, K, F>
+where
+    K: Clone + Eq + Hash + fmt::Debug + 'static,
+    F: Fn(&mut State, DrawEvent<K>) + 'static,
+{
+    DrawControl {
+        shapes,
+        options,
+        on_event,
+        phantom: PhantomData,
+    }
+}
+
+pub struct DrawControl<State, K, F> {
+    shapes: Vec<(K, Shape)>,
+    options: DrawOptions,
+    on_event: F,
+    phantom: PhantomData<fn() -> State>,
+}
+
+impl<State, K, F> ViewMarker for DrawControl<State, K, F> {}
+
+pub struct DrawControlViewState<K> {
+    layer: Rc<RefCell<DrawLayer<K>>>,
+    listeners: Vec<EventListener<leaflet::Map>>,
+}
+
+struct DrawLayer<K> {
+    map: leaflet::Map,
+    thunk: Rc<MessageThunk>,
+    drawing: Drawing,
+    shapes: HashMap<K, DrawnShape>,
+    /// Preview of the shape that is drawn or edited.
+    sketch: leaflet::Polyline,
+    selection: Option<Selection<K>>,
+    dragging: PausedHandler,
+    double_click_zoom: PausedHandler,
+}
+
+/// An interaction handler of the map that is disabled while a tool is active.
+struct PausedHandler {
+    name: &'static str,
+    /// Whether the handler was enabled before it has been disabled (`None` if it's not disabled).
+    was_enabled: Option<bool>,
+}
+
+impl PausedHandler {
+    const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            was_enabled: None,
+        }
+    }
+
+    /// Disable the handler or restore its previous state.
+    fn pause(&mut self, map: &leaflet::Map, paused: bool) {
+        match (paused, self.was_enabled) {
+            (true, None) => {
+                self.was_enabled = Some(handler_enabled(map, self.name));
+                set_handler_enabled(map, self.name, false);
+            }
+            (false, Some(was_enabled)) => {
+                self.was_enabled = None;
+                if was_enabled {
+                    set_handler_enabled(map, self.name, true);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+struct DrawnShape {
+    shape: Shape,
+    layer: leaflet::Layer,
+    _on_click: EventListener<leaflet::Layer>,
+}
+
+/// The shape that is edited.
+struct Selection<K> {
+    key: K,
+    shape: Shape,
+    handles: Vec<Handle>,
+}
+
+struct Handle {
+    marker: leaflet::Marker,
+    _listeners: [EventListener<leaflet::Marker>; 2],
+}
+
+impl<K> DrawLayer<K>
+where
+    K: Clone + Eq + Hash + fmt::Debug + 'static,
+{
+    fn set_shapes(&mut self, shapes: &[(K, Shape)], this: &Weak<RefCell<Self>>) {
+        let mut drawn_shapes = HashMap::with_capacity(shapes.len());
+        for (key, shape) in shapes {
+            let drawn_shape = match self.shapes.remove(key) {
+                Some(drawn_shape) if drawn_shape.shape == *shape => drawn_shape,
+                prev => {
+                    if let Some(prev) = prev {
+                        prev.layer.remove();
+                    }
+                    self.create_shape(key, shape, this)
+                }
+            };
+            if drawn_shapes.insert(key.clone(), drawn_shape).is_some() {
+                log::warn!("Duplicate shape key {key:?}");
+            }
+        }
+        self.clear_shapes();
+        self.shapes = drawn_shapes;
+        // The selected shape might have been changed or removed.
+        if let Some(selection) = &self.selection {
+            match self.shapes.get(&selection.key) {
+                Some(drawn_shape) if drawn_shape.shape == selection.shape => {}
+                Some(drawn_shape) => {
+                    let key = selection.key.clone();
+                    let shape = drawn_shape.shape.clone();
+                    self.select(key, shape, this);
+                }
+                None => self.deselect(),
+            }
+        }
+    }
+
+    fn create_shape(&self, key: &K, shape: &Shape, this: &Weak<RefCell<Self>>) -> DrawnShape {
+        let layer = create_layer(shape);
+        layer.add_to(&self.map);
+        let on_click = {
+            let key = key.clone();
+            let this = Weak::clone(this);
+            EventListener::new(&layer, "click", move |ev: leaflet::MouseEvent| {
+                let Some(this) = this.upgrade() else {
+                    return;
+                };
+                let (tool, thunk, shape) = {
+                    let layer = this.borrow();
+                    let shape = layer.shapes.get(&key).map(|s| s.shape.clone());
+                    (layer.drawing.tool(), Rc::clone(&layer.thunk), shape)
+                };
+                // The click on the shape must not e.g. place a marker or deselect the shape,
+                // but the vertices of lines can be placed on other shapes.
+                if tool.is_some_and(|tool| !matches!(tool, DrawTool::Polyline | DrawTool::Polygon))
+                {
+                    stop_propagation(&ev);
+                }
+                match (tool, shape) {
+                    (Some(DrawTool::Edit), Some(shape)) => {
+                        this.borrow_mut()
+                            .select(key.clone(), shape, &Rc::downgrade(&this));
+                    }
+                    (Some(DrawTool::Delete), Some(_)) => {
+                        thunk.push_message(DrawEvent::Deleted(key.clone()));
+                    }
+                    _ => {}
+                }
+            })
+        };
+        DrawnShape {
+            shape: shape.clone(),
+            layer,
+            _on_click: on_click,
+        }
+    }
+
+    fn select(&mut self, key: K, shape: Shape, this: &Weak<RefCell<Self>>) {
+        self.deselect();
+        let handles = shape
+            .handles()
+            .into_iter()
+            .enumerate()
+            .map(|(index, position)| self.create_handle(index, position, this))
+            .collect();
+        self.selection = Some(Selection {
+            key,
+            shape,
+            handles,
+        });
+    }
+
+    fn create_handle(&self, index: usize, position: LatLng, this: &Weak<RefCell<Self>>) -> Handle {
+        let marker = create_handle_marker(&self.map, position, DRAW_HANDLE_CLASS_NAME);
+        let on_drag = {
+            let this = Weak::clone(this);
+            let handle_marker = marker.clone();
+            EventListener::new(&marker, "drag", move |_: leaflet::Event| {
+                let Some(this) = this.upgrade() else {
+                    return;
+                };
+                let layer = this.borrow();
+                let position = handle_marker.get_lat_lng().into();
+                if let Some(shape) = layer.edited_shape(index, position) {
+                    layer.draw_sketch(&shape.outline());
+                }
+            })
+        };
+        let on_drag_end = {
+            let this = Weak::clone(this);
+            let handle_marker = marker.clone();
+            EventListener::new(&marker, "dragend", move |_: leaflet::Event| {
+                let Some(this) = this.upgrade() else {
+                    return;
+                };
+                let (event, thunk) = {
+                    let layer = this.borrow();
+                    let Some(selection) = &layer.selection else {
+                        return;
+                    };
+                    let position = handle_marker.get_lat_lng().into();
+                    let Some(shape) = layer.edited_shape(index, position) else {
+                        return;
+                    };
+                    // Show the current shape until it has been changed by a rebuild.
+                    layer.draw_sketch(&[]);
+                    for (handle, position) in
+                        selection.handles.iter().zip(selection.shape.handles())
+                    {
+                        handle.marker.set_lat_lng(&position.into());
+                    }
+                    (
+                        DrawEvent::Edited(selection.key.clone(), shape),
+                        Rc::clone(&layer.thunk),
+                    )
+                };
+                thunk.push_message(event);
+            })
+        };
+        Handle {
+            marker,
+            _listeners: [on_drag, on_drag_end],
+        }
+    }
+
+    fn edited_shape(&self, index: usize, position: LatLng) -> Option<Shape> {
+        let mut shape = self.selection.as_ref()?.shape.clone();
+        shape.move_handle(index, position);
+        Some(shape)
+    }
+
+    fn deselect(&mut self) {
+        if let Some(selection) = self.selection.take() {
+            for handle in selection.handles {
+                handle.marker.remove();
+            }
+        }
+    }
+
+    fn draw_sketch(&self, points: &[LatLng]) {
+        self.sketch.set_lat_lngs(&lat_lng_array(points));
+    }
+
+    fn set_tool(&mut self, tool: Option<DrawTool>) {
+        self.cancel_drawing();
+        self.deselect();
+        let draws_areas = matches!(tool, Some(DrawTool::Rectangle | DrawTool::Circle));
+        let draws_lines = matches!(tool, Some(DrawTool::Polyline | DrawTool::Polygon));
+        // Dragging the map and zooming by double click would interfere with drawing.
+        self.dragging.pause(&self.map, draws_areas);
+        self.double_click_zoom.pause(&self.map, draws_lines);
+        self.drawing.set_tool(tool);
+    }
+
+    fn cancel_drawing(&mut self) {
+        self.drawing.cancel();
+        self.draw_sketch(&[]);
+    }
+
+    fn clear_shapes(&mut self) {
+        for (_, drawn_shape) in self.shapes.drain() {
+            drawn_shape.layer.remove();
+        }
+    }
+
+    /// Handle a map event and return the shape that has been created (if any).
+    fn handle_map_event(&mut self, event_type: &str, position: LatLng) -> Option<Shape> {
+        match self.drawing.handle_event(event_type, position) {
+            DrawStep::Ignore => None,
+            DrawStep::Deselect => {
+                self.deselect();
+                None
+            }
+            DrawStep::Sketch(points) => {
+                self.draw_sketch(&points);
+                None
+            }
+            DrawStep::Created(shape) => {
+                self.draw_sketch(&[]);
+                Some(shape)
+            }
+        }
+    }
+}
+
+/// Create a draggable marker that is used as handle for editing.
+pub(crate) fn create_handle_marker(
+    map: &leaflet::Map,
+    position: LatLng,
+    class_name: &str,
+) -> leaflet::Marker {
+    let icon_options = leaflet::DivIconOptions::new();
+    icon_options.set_class_name(class_name.to_string());
+    icon_options.set_icon_size(leaflet::Point::new(HANDLE_SIZE, HANDLE_SIZE));
+    let marker_options = leaflet::MarkerOptions::new();
+    marker_options.set_icon(leaflet::DivIcon::new(&icon_options).into());
+    marker_options.set_draggable(true);
+    let marker = leaflet::Marker::new_with_options(&position.into(), &marker_options);
+    marker.add_to(map);
+    marker
+}
+
+fn create_layer(shape: &Shape) -> leaflet::Layer {
+    match shape {
+        Shape::Marker(position) => leaflet::Marker::new(&(*position).into()).unchecked_into(),
+        Shape::Polyline(points) => leaflet::Polyline::new(&lat_lng_array(points)).unchecked_into(),
+        Shape::Polygon(points) => leaflet::Polygon::new(&lat_lng_array(points)).unchecked_into(),
+        Shape::Rectangle(bounds) => leaflet::Rectangle::new(&(*bounds).into()).unchecked_into(),
+        Shape::Circle { center, radius } => {
+            let options = leaflet::CircleOptions::new();
+            options.set_radius(*radius);
+            leaflet::Circle::new_with_options(&(*center).into(), &options).unchecked_into()
+        }
+    }
+}
+
+impl<State, Action, K, F> View<State, Action, MapCtx, DynMessage> for DrawControl<State, K, F>
+where
+    State: 'static,
+    K: Clone + Eq + Hash + fmt::Debug + 'static,
+    F: Fn(&mut State, DrawEvent<K>) + 'static,
+{
+    type Element = MapChildElement;
+
+    type ViewState = DrawControlViewState<K>;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        ctx.with_id(DRAW_CONTROL_ID, |ctx| {
+            let map = ctx.map();
+            let sketch = leaflet::Polyline::new(&Array::new());
+            sketch.add_to(map);
+            let layer = Rc::new(RefCell::new(DrawLayer {
+                map: map.clone(),
+                thunk: Rc::new(ctx.message_thunk()),
+                drawing: Drawing::new(),
+                shapes: HashMap::new(),
+                sketch,
+                selection: None,
+                dragging: PausedHandler::new("dragging"),
+                double_click_zoom: PausedHandler::new("doubleClickZoom"),
+            }));
+            {
+                let mut layer_mut = layer.borrow_mut();
+                layer_mut.set_shapes(&self.shapes, &Rc::downgrade(&layer));
+                layer_mut.set_tool(self.options.tool);
+            }
+            let listeners = ["click", "dblclick", "mousedown", "mousemove", "mouseup"]
+                .into_iter()
+                .map(|event_type| {
+                    let layer = Rc::clone(&layer);
+                    EventListener::new(map, event_type, move |ev: leaflet::MouseEvent| {
+                        // The borrow must be released before the message is handled.
+                        let (created, thunk) = {
+                            let mut layer = layer.borrow_mut();
+                            let created = layer.handle_map_event(event_type, ev.lat_lng().into());
+                            (created, Rc::clone(&layer.thunk))
+                        };
+                        if let Some(shape) = created {
+                            thunk.push_message(DrawEvent::<K>::Created(shape));
+                        }
+                    })
+                })
+                .collect();
+            let view_state = DrawControlViewState { layer, listeners };
+            (MapChildElement::Group, view_state)
+        })
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx,
+        _: Mut<Self::Element>,
+    ) {
+        ctx.with_id(DRAW_CONTROL_ID, |_| {
+            let mut layer = view_state.layer.borrow_mut();
+            if prev.options != self.options {
+                layer.set_tool(self.options.tool);
+            }
+            if prev.shapes != self.shapes {
+                layer.set_shapes(&self.shapes, &Rc::downgrade(&view_state.layer));
+            }
+        });
+    }
+
+    fn teardown(&self, view_state: &mut Self::ViewState, ctx: &mut MapCtx, _: Mut<Self::Element>) {
+        ctx.with_id(DRAW_CONTROL_ID, |_| {
+            view_state.listeners.clear();
+            let mut layer = view_state.layer.borrow_mut();
+            layer.set_tool(None);
+            layer.clear_shapes();
+            layer.sketch.remove();
+        });
+    }
+
+    fn message(
+        &self,
+        _: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        state: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        debug_assert!(id_path.len() == 1 && id_path[0] == DRAW_CONTROL_ID);
+        let event = *message.downcast::<DrawEvent<K>>().unwrap_throw();
+        (self.on_event)(state, event);
+        MessageResult::Nop
+    }
+}
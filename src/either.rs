@@ -0,0 +1,138 @@
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker as _},
+    DynMessage,
+};
+
+use crate::{MapBackend, MapChildElement, MapCtx};
+
+/// A map child that is one of two views,
+/// e.g. to show different layers depending on a mode.
+///
+/// When the variant changes, the previous view is torn down
+/// and the new one is built in its place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+impl<L, R> ViewMarker for Either<L, R> {}
+
+pub struct EitherViewState<L, R> {
+    /// Incremented when the variant changes
+    /// so that messages for the previous view are stale.
+    generation: u64,
+    inner: Either<L, R>,
+}
+
+impl<State, Action, L, R, B> View<State, Action, MapCtx<B>, DynMessage> for Either<L, R>
+where
+    State: 'static,
+    Action: 'static,
+    L: View<State, Action, MapCtx<B>, DynMessage, Element = MapChildElement<B>>,
+    R: View<State, Action, MapCtx<B>, DynMessage, Element = MapChildElement<B>>,
+    B: MapBackend,
+{
+    type Element = MapChildElement<B>;
+
+    type ViewState = EitherViewState<L::ViewState, R::ViewState>;
+
+    fn build(&self, ctx: &mut MapCtx<B>) -> (Self::Element, Self::ViewState) {
+        let generation = 0;
+        let (element, inner) = build_variant::<State, Action, _, _, _>(self, generation, ctx);
+        (element, EitherViewState { generation, inner })
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx<B>,
+        element: Mut<Self::Element>,
+    ) {
+        let id = ViewId::new(view_state.generation);
+        match (self, prev, &mut view_state.inner) {
+            (Self::Left(view), Self::Left(prev), Either::Left(state)) => {
+                ctx.with_id(id, |ctx| view.rebuild(prev, state, ctx, element));
+                return;
+            }
+            (Self::Right(view), Self::Right(prev), Either::Right(state)) => {
+                ctx.with_id(id, |ctx| view.rebuild(prev, state, ctx, element));
+                return;
+            }
+            (_, Self::Left(prev), Either::Left(state)) => {
+                ctx.with_id(id, |ctx| prev.teardown(state, ctx, &mut *element));
+            }
+            (_, Self::Right(prev), Either::Right(state)) => {
+                ctx.with_id(id, |ctx| prev.teardown(state, ctx, &mut *element));
+            }
+            _ => unreachable!("the view state does not match the previous view"),
+        }
+        view_state.generation = view_state.generation.wrapping_add(1);
+        let (new_element, inner) =
+            build_variant::<State, Action, _, _, _>(self, view_state.generation, ctx);
+        *element = new_element;
+        view_state.inner = inner;
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx<B>,
+        element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(ViewId::new(view_state.generation), |ctx| {
+            match (self, &mut view_state.inner) {
+                (Self::Left(view), Either::Left(state)) => view.teardown(state, ctx, element),
+                (Self::Right(view), Either::Right(state)) => view.teardown(state, ctx, element),
+                _ => unreachable!("the view state does not match the view"),
+            }
+        });
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        let Some((first, rest)) = id_path.split_first() else {
+            return MessageResult::Stale(message);
+        };
+        if first.routing_id() != view_state.generation {
+            return MessageResult::Stale(message);
+        }
+        match (self, &mut view_state.inner) {
+            (Self::Left(view), Either::Left(state)) => {
+                view.message(state, rest, message, app_state)
+            }
+            (Self::Right(view), Either::Right(state)) => {
+                view.message(state, rest, message, app_state)
+            }
+            _ => unreachable!("the view state does not match the view"),
+        }
+    }
+}
+
+fn build_variant<State, Action, L, R, B>(
+    view: &Either<L, R>,
+    generation: u64,
+    ctx: &mut MapCtx<B>,
+) -> (MapChildElement<B>, Either<L::ViewState, R::ViewState>)
+where
+    L: View<State, Action, MapCtx<B>, DynMessage, Element = MapChildElement<B>>,
+    R: View<State, Action, MapCtx<B>, DynMessage, Element = MapChildElement<B>>,
+    B: MapBackend,
+{
+    ctx.with_id(ViewId::new(generation), |ctx| match view {
+        Either::Left(view) => {
+            let (element, state) = view.build(ctx);
+            (element, Either::Left(state))
+        }
+        Either::Right(view) => {
+            let (element, state) = view.build(ctx);
+            (element, Either::Right(state))
+        }
+    })
+}
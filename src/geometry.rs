@@ -0,0 +1,500 @@
+//! Geographical and pixel coordinates.
+//!
+//! The types do not depend on Leaflet and can be used natively.
+//! They can be converted from and into the corresponding types of Leaflet
+//! and (with the `geo-types` feature) of the [`geo_types`] crate.
+//! The fallible conversions into [`LatLng`] validate the point,
+//! [`LatLng::new`] and the conversions from Leaflet don't,
+//! since the coordinates of a non-geographic CRS can exceed the latitude range.
+
+use std::fmt;
+
+/// Minimum/maximum latitude in degrees.
+const MAX_LATITUDE: f64 = 90.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidLatLng {
+    NotANumber,
+    LatitudeOutOfRange,
+    LongitudeNotFinite,
+}
+
+impl fmt::Display for InvalidLatLng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotANumber => f.write_str("latitude or longitude is not a number"),
+            Self::LatitudeOutOfRange => write!(
+                f,
+                "latitude is not within the range [-{MAX_LATITUDE}, {MAX_LATITUDE}]"
+            ),
+            Self::LongitudeNotFinite => f.write_str("longitude is not finite"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidLatLng {}
+
+/// A geographical point in degrees
+/// (or a point of a non-geographic CRS like [`Crs::Simple`](crate::Crs::Simple)).
+///
+/// Deserializing a point that is not [valid](LatLng::validate) fails.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "UncheckedLatLng")
+)]
+pub struct LatLng {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+impl LatLng {
+    /// Create a point without validating it (see [`LatLng::try_new`]).
+    #[must_use]
+    pub const fn new(lat: f64, lng: f64) -> Self {
+        Self { lat, lng }
+    }
+
+    /// Create a point with a latitude within `[-90, 90]` and a finite longitude.
+    pub fn try_new(lat: f64, lng: f64) -> Result<Self, InvalidLatLng> {
+        let lat_lng = Self { lat, lng };
+        lat_lng.validate()?;
+        Ok(lat_lng)
+    }
+
+    pub fn validate(&self) -> Result<(), InvalidLatLng> {
+        if self.lat.is_nan() || self.lng.is_nan() {
+            return Err(InvalidLatLng::NotANumber);
+        }
+        if !(-MAX_LATITUDE..=MAX_LATITUDE).contains(&self.lat) {
+            return Err(InvalidLatLng::LatitudeOutOfRange);
+        }
+        if !self.lng.is_finite() {
+            return Err(InvalidLatLng::LongitudeNotFinite);
+        }
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+}
+
+impl TryFrom<(f64, f64)> for LatLng {
+    type Error = InvalidLatLng;
+
+    fn try_from((lat, lng): (f64, f64)) -> Result<Self, Self::Error> {
+        Self::try_new(lat, lng)
+    }
+}
+
+impl From<LatLng> for (f64, f64) {
+    fn from(LatLng { lat, lng }: LatLng) -> Self {
+        (lat, lng)
+    }
+}
+
+/// The fields of a [`LatLng`] before the validation.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "LatLng")]
+struct UncheckedLatLng {
+    lat: f64,
+    lng: f64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<UncheckedLatLng> for LatLng {
+    type Error = InvalidLatLng;
+
+    fn try_from(UncheckedLatLng { lat, lng }: UncheckedLatLng) -> Result<Self, Self::Error> {
+        Self::try_new(lat, lng)
+    }
+}
+
+/// A rectangular geographical area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LatLngBounds {
+    pub south_west: LatLng,
+    pub north_east: LatLng,
+}
+
+impl LatLngBounds {
+    /// Create the bounds from two diagonally opposite corners.
+    #[must_use]
+    pub fn new(corner1: LatLng, corner2: LatLng) -> Self {
+        Self {
+            south_west: LatLng::new(corner1.lat.min(corner2.lat), corner1.lng.min(corner2.lng)),
+            north_east: LatLng::new(corner1.lat.max(corner2.lat), corner1.lng.max(corner2.lng)),
+        }
+    }
+
+    /// The smallest bounds that contain all points.
+    #[must_use]
+    pub fn from_points(points: impl IntoIterator<Item = LatLng>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut bounds = Self::new(first, first);
+        for point in points {
+            bounds.extend(point);
+        }
+        Some(bounds)
+    }
+
+    pub fn extend(&mut self, point: LatLng) {
+        *self = Self::new(
+            LatLng::new(
+                self.south_west.lat.min(point.lat),
+                self.south_west.lng.min(point.lng),
+            ),
+            LatLng::new(
+                self.north_east.lat.max(point.lat),
+                self.north_east.lng.max(point.lng),
+            ),
+        );
+    }
+
+    #[must_use]
+    pub fn contains(&self, point: LatLng) -> bool {
+        point.lat >= self.south_west.lat
+            && point.lat <= self.north_east.lat
+            && point.lng >= self.south_west.lng
+            && point.lng <= self.north_east.lng
+    }
+
+    #[must_use]
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.south_west.lat <= other.north_east.lat
+            && self.north_east.lat >= other.south_west.lat
+            && self.south_west.lng <= other.north_east.lng
+            && self.north_east.lng >= other.south_west.lng
+    }
+
+    #[must_use]
+    pub fn center(&self) -> LatLng {
+        LatLng::new(
+            (self.south_west.lat + self.north_east.lat) / 2.0,
+            (self.south_west.lng + self.north_east.lng) / 2.0,
+        )
+    }
+
+    #[must_use]
+    pub const fn south(&self) -> f64 {
+        self.south_west.lat
+    }
+
+    #[must_use]
+    pub const fn west(&self) -> f64 {
+        self.south_west.lng
+    }
+
+    #[must_use]
+    pub const fn north(&self) -> f64 {
+        self.north_east.lat
+    }
+
+    #[must_use]
+    pub const fn east(&self) -> f64 {
+        self.north_east.lng
+    }
+}
+
+/// A point in pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    #[must_use]
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<(f64, f64)> for Point {
+    fn from((x, y): (f64, f64)) -> Self {
+        Self { x, y }
+    }
+}
+
+/// A rectangular area in pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    /// Create the bounds from two diagonally opposite corners.
+    #[must_use]
+    pub fn new(corner1: Point, corner2: Point) -> Self {
+        Self {
+            min: Point::new(corner1.x.min(corner2.x), corner1.y.min(corner2.y)),
+            max: Point::new(corner1.x.max(corner2.x), corner1.y.max(corner2.y)),
+        }
+    }
+
+    #[must_use]
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    #[must_use]
+    pub fn size(&self) -> Point {
+        Point::new(self.max.x - self.min.x, self.max.y - self.min.y)
+    }
+
+    #[must_use]
+    pub fn center(&self) -> Point {
+        Point::new(
+            (self.min.x + self.max.x) / 2.0,
+            (self.min.y + self.max.y) / 2.0,
+        )
+    }
+}
+
+// Leaflet
+
+impl From<LatLng> for leaflet::LatLng {
+    fn from(LatLng { lat, lng }: LatLng) -> Self {
+        Self::new(lat, lng)
+    }
+}
+
+impl From<&leaflet::LatLng> for LatLng {
+    fn from(lat_lng: &leaflet::LatLng) -> Self {
+        Self::new(lat_lng.lat(), lat_lng.lng())
+    }
+}
+
+impl From<leaflet::LatLng> for LatLng {
+    fn from(lat_lng: leaflet::LatLng) -> Self {
+        Self::from(&lat_lng)
+    }
+}
+
+/// Convert the points into a JS array of Leaflet points (e.g. for polylines).
+pub(crate) fn lat_lng_array(points: &[LatLng]) -> web_sys::js_sys::Array {
+    points
+        .iter()
+        .map(|&point| web_sys::wasm_bindgen::JsValue::from(leaflet::LatLng::from(point)))
+        .collect()
+}
+
+impl From<LatLngBounds> for leaflet::LatLngBounds {
+    fn from(bounds: LatLngBounds) -> Self {
+        Self::new(&bounds.south_west.into(), &bounds.north_east.into())
+    }
+}
+
+impl From<&leaflet::LatLngBounds> for LatLngBounds {
+    fn from(bounds: &leaflet::LatLngBounds) -> Self {
+        Self::new(
+            bounds.get_south_west().into(),
+            bounds.get_north_east().into(),
+        )
+    }
+}
+
+impl From<leaflet::LatLngBounds> for LatLngBounds {
+    fn from(bounds: leaflet::LatLngBounds) -> Self {
+        Self::from(&bounds)
+    }
+}
+
+impl From<Point> for leaflet::Point {
+    fn from(Point { x, y }: Point) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl From<&leaflet::Point> for Point {
+    fn from(point: &leaflet::Point) -> Self {
+        Self::new(point.x(), point.y())
+    }
+}
+
+impl From<leaflet::Point> for Point {
+    fn from(point: leaflet::Point) -> Self {
+        Self::from(&point)
+    }
+}
+
+impl From<Bounds> for leaflet::Bounds {
+    fn from(bounds: Bounds) -> Self {
+        Self::new(&bounds.min.into(), &bounds.max.into())
+    }
+}
+
+impl From<&leaflet::Bounds> for Bounds {
+    fn from(bounds: &leaflet::Bounds) -> Self {
+        Self::new(
+            bounds.get_top_left().into(),
+            bounds.get_bottom_right().into(),
+        )
+    }
+}
+
+impl From<leaflet::Bounds> for Bounds {
+    fn from(bounds: leaflet::Bounds) -> Self {
+        Self::from(&bounds)
+    }
+}
+
+// geo-types
+
+#[cfg(feature = "geo-types")]
+mod geo_types_conversions {
+    use super::{Bounds, InvalidLatLng, LatLng, LatLngBounds, Point};
+
+    impl From<LatLng> for geo_types::Coord {
+        fn from(LatLng { lat, lng }: LatLng) -> Self {
+            Self { x: lng, y: lat }
+        }
+    }
+
+    impl TryFrom<geo_types::Coord> for LatLng {
+        type Error = InvalidLatLng;
+
+        fn try_from(geo_types::Coord { x, y }: geo_types::Coord) -> Result<Self, Self::Error> {
+            Self::try_new(y, x)
+        }
+    }
+
+    impl From<LatLng> for geo_types::Point {
+        fn from(lat_lng: LatLng) -> Self {
+            Self(lat_lng.into())
+        }
+    }
+
+    impl TryFrom<geo_types::Point> for LatLng {
+        type Error = InvalidLatLng;
+
+        fn try_from(point: geo_types::Point) -> Result<Self, Self::Error> {
+            point.0.try_into()
+        }
+    }
+
+    impl From<LatLngBounds> for geo_types::Rect {
+        fn from(bounds: LatLngBounds) -> Self {
+            Self::new(bounds.south_west, bounds.north_east)
+        }
+    }
+
+    impl TryFrom<geo_types::Rect> for LatLngBounds {
+        type Error = InvalidLatLng;
+
+        fn try_from(rect: geo_types::Rect) -> Result<Self, Self::Error> {
+            Ok(Self::new(rect.min().try_into()?, rect.max().try_into()?))
+        }
+    }
+
+    impl From<Point> for geo_types::Coord {
+        fn from(Point { x, y }: Point) -> Self {
+            Self { x, y }
+        }
+    }
+
+    impl From<geo_types::Coord> for Point {
+        fn from(geo_types::Coord { x, y }: geo_types::Coord) -> Self {
+            Self::new(x, y)
+        }
+    }
+
+    impl From<Bounds> for geo_types::Rect {
+        fn from(bounds: Bounds) -> Self {
+            Self::new(bounds.min, bounds.max)
+        }
+    }
+
+    impl From<geo_types::Rect> for Bounds {
+        fn from(rect: geo_types::Rect) -> Self {
+            Self::new(rect.min().into(), rect.max().into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_lat_lng() {
+        assert!(LatLng::try_new(90.0, 540.0).is_ok());
+        assert_eq!(
+            LatLng::try_new(f64::NAN, 0.0),
+            Err(InvalidLatLng::NotANumber)
+        );
+        assert_eq!(
+            LatLng::try_new(0.0, f64::NAN),
+            Err(InvalidLatLng::NotANumber)
+        );
+        assert_eq!(
+            LatLng::try_new(-90.1, 0.0),
+            Err(InvalidLatLng::LatitudeOutOfRange)
+        );
+        assert_eq!(
+            LatLng::try_new(0.0, f64::INFINITY),
+            Err(InvalidLatLng::LongitudeNotFinite)
+        );
+        assert!(!LatLng {
+            lat: 91.0,
+            lng: 0.0
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn new_lat_lng_of_a_non_geographic_crs() {
+        let lat_lng = LatLng::new(500.0, 800.0);
+        assert_eq!((lat_lng.lat, lat_lng.lng), (500.0, 800.0));
+        assert!(!lat_lng.is_valid());
+    }
+
+    #[test]
+    fn try_from_tuple() {
+        assert_eq!(LatLng::try_from((1.5, 2.5)), Ok(LatLng::new(1.5, 2.5)));
+        assert_eq!(
+            LatLng::try_from((0.0, f64::NAN)),
+            Err(InvalidLatLng::NotANumber)
+        );
+        assert_eq!(
+            LatLng::try_from((500.0, 800.0)),
+            Err(InvalidLatLng::LatitudeOutOfRange)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_lat_lng() {
+        let lat_lng: LatLng = serde_json::from_str(r#"{"lat":1.5,"lng":2.5}"#).unwrap();
+        assert_eq!(lat_lng, LatLng::new(1.5, 2.5));
+        assert!(serde_json::from_str::<LatLng>(r#"{"lat":100,"lng":0}"#).is_err());
+        assert!(serde_json::from_str::<LatLngBounds>(
+            r#"{"south_west":{"lat":-91,"lng":0},"north_east":{"lat":0,"lng":0}}"#
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "geo-types")]
+    #[test]
+    fn from_geo_types() {
+        let coord = geo_types::Coord { x: 2.5, y: 1.5 };
+        assert_eq!(LatLng::try_from(coord), Ok(LatLng::new(1.5, 2.5)));
+        let coord = geo_types::Coord { x: 0.0, y: 95.0 };
+        assert_eq!(
+            LatLng::try_from(coord),
+            Err(InvalidLatLng::LatitudeOutOfRange)
+        );
+    }
+}
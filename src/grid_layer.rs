@@ -0,0 +1,169 @@
+use std::{cell::RefCell, rc::Rc};
+
+use web_sys::{
+    js_sys::Reflect,
+    wasm_bindgen::{closure::Closure, JsValue, UnwrapThrowExt as _},
+};
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker},
+    DynMessage,
+};
+
+use crate::{
+    canvas_overlay::create_canvas,
+    map::TrackedClosure,
+    projection::{TileCoords, TILE_SIZE},
+    MapChildElement, MapCtx,
+};
+
+#[allow(clippy::cast_possible_truncation)]
+fn tile_coords_from_js(coords: &JsValue) -> TileCoords {
+    let get = |property: &str| {
+        Reflect::get(coords, &property.into())
+            .ok()
+            .and_then(|value| value.as_f64())
+            .unwrap_or_default() as i32
+    };
+    TileCoords {
+        x: get("x"),
+        y: get("y"),
+        z: get("z"),
+    }
+}
+
+/// Render the tiles of a Leaflet `GridLayer` with a Rust function.
+///
+/// The function is called for each tile with its coordinates
+/// and a canvas of the size of a tile to draw on.
+/// The tiles are cached by Leaflet and only rendered again if the `key` changes.
+/// New tiles (e.g. after panning) are always rendered with the latest `render_tile`.
+pub const fn grid_layer<K, F>(key: K, render_tile: F) -> GridLayer<K, F>
+where
+    K: PartialEq + 'static,
+    F: Fn(TileCoords, &web_sys::HtmlCanvasElement) + Clone + 'static,
+{
+    GridLayer {
+        key,
+        render_tile,
+        tile_size: TILE_SIZE,
+    }
+}
+
+pub struct GridLayer<K, F> {
+    key: K,
+    render_tile: F,
+    tile_size: f64,
+}
+
+impl<K, F> GridLayer<K, F> {
+    /// The width and height of a tile in pixels.
+    #[must_use]
+    pub const fn tile_size(mut self, tile_size: f64) -> Self {
+        self.tile_size = tile_size;
+        self
+    }
+}
+
+impl<K, F> ViewMarker for GridLayer<K, F> {}
+
+type CreateTile = TrackedClosure<dyn Fn(JsValue) -> JsValue>;
+
+pub struct GridLayerViewState<F> {
+    render_tile: Rc<RefCell<F>>,
+    _create_tile: CreateTile,
+}
+
+fn create_grid_layer<F>(
+    render_tile: &Rc<RefCell<F>>,
+    tile_size: f64,
+) -> (leaflet::GridLayer, CreateTile)
+where
+    F: Fn(TileCoords, &web_sys::HtmlCanvasElement) + 'static,
+{
+    let options = leaflet::GridLayerOptions::new();
+    options.set_tile_size(tile_size);
+    let layer = leaflet::GridLayer::new_options(&options);
+    let create_tile = {
+        let render_tile = Rc::clone(render_tile);
+        Closure::<dyn Fn(JsValue) -> JsValue>::new(move |coords: JsValue| {
+            let canvas = create_canvas();
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            {
+                canvas.set_width(tile_size as u32);
+                canvas.set_height(tile_size as u32);
+            }
+            (render_tile.borrow())(tile_coords_from_js(&coords), &canvas);
+            canvas.into()
+        })
+    };
+    // Overriding `createTile` of the instance
+    // is equivalent to extending `L.GridLayer`.
+    Reflect::set(&layer, &"createTile".into(), create_tile.as_ref()).unwrap_throw();
+    (layer, TrackedClosure::new(create_tile))
+}
+
+impl<State, Action, K, F> View<State, Action, MapCtx, DynMessage> for GridLayer<K, F>
+where
+    K: PartialEq + 'static,
+    F: Fn(TileCoords, &web_sys::HtmlCanvasElement) + Clone + 'static,
+{
+    type Element = MapChildElement;
+
+    type ViewState = GridLayerViewState<F>;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        let render_tile = Rc::new(RefCell::new(self.render_tile.clone()));
+        let (layer, create_tile) = create_grid_layer(&render_tile, self.tile_size);
+        layer.add_to(ctx.map());
+        let view_state = GridLayerViewState {
+            render_tile,
+            _create_tile: create_tile,
+        };
+        (MapChildElement::GridLayer(layer), view_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx,
+        element: Mut<Self::Element>,
+    ) {
+        // The function might capture changed state even if the key is unchanged.
+        *view_state.render_tile.borrow_mut() = self.render_tile.clone();
+        if prev.key == self.key && prev.tile_size == self.tile_size {
+            return;
+        }
+        let Some(layer) = element.as_grid_layer_mut() else {
+            log::error!("The element of a grid layer is not a grid layer: {element:?}");
+            return;
+        };
+        if prev.tile_size == self.tile_size {
+            layer.redraw();
+            return;
+        }
+        layer.remove();
+        let (new_layer, create_tile) = create_grid_layer(&view_state.render_tile, self.tile_size);
+        new_layer.add_to(ctx.map());
+        *layer = new_layer;
+        view_state._create_tile = create_tile;
+    }
+
+    fn teardown(&self, _: &mut Self::ViewState, _: &mut MapCtx, element: Mut<Self::Element>) {
+        let Some(layer) = element.as_grid_layer_mut() else {
+            log::error!("The element of a grid layer is not a grid layer: {element:?}");
+            return;
+        };
+        layer.remove();
+    }
+
+    fn message(
+        &self,
+        _: &mut Self::ViewState,
+        _: &[ViewId],
+        message: DynMessage,
+        _: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        MessageResult::Stale(message)
+    }
+}
@@ -0,0 +1,188 @@
+use std::{cell::RefCell, rc::Rc};
+
+use web_sys::wasm_bindgen::{Clamped, UnwrapThrowExt as _};
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker},
+    DynMessage,
+};
+
+use crate::{
+    canvas_overlay::CanvasOverlay,
+    map::EventListener,
+    projection::{lat_y, lng_x},
+    DensityKernel, DensityRaster, Gradient, LatLng, MapChildElement, MapCtx,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapOptions {
+    /// Radius of a point in pixels.
+    pub radius: f64,
+    /// Part of the radius (from `0.0` to `1.0`) over which a point fades out.
+    pub blur: f64,
+    pub gradient: Gradient,
+    /// The intensity that corresponds to the end of the gradient.
+    ///
+    /// If it's `None`, the maximum intensity within the visible area is used.
+    pub max: Option<f64>,
+    pub min_opacity: f64,
+}
+
+impl Default for HeatmapOptions {
+    fn default() -> Self {
+        let DensityKernel { radius, blur } = DensityKernel::default();
+        Self {
+            radius,
+            blur,
+            gradient: Gradient::default(),
+            max: None,
+            min_opacity: 0.05,
+        }
+    }
+}
+
+impl HeatmapOptions {
+    const fn kernel(&self) -> DensityKernel {
+        DensityKernel {
+            radius: self.radius,
+            blur: self.blur,
+        }
+    }
+}
+
+/// Display the density of weighted points.
+///
+/// The density is calculated for the visible area
+/// and rendered again when the map has been moved or zoomed.
+pub const fn heatmap(points: Vec<(LatLng, f64)>, options: HeatmapOptions) -> Heatmap {
+    Heatmap { points, options }
+}
+
+pub struct Heatmap {
+    points: Vec<(LatLng, f64)>,
+    options: HeatmapOptions,
+}
+
+impl ViewMarker for Heatmap {}
+
+pub struct HeatmapViewState {
+    layer: Rc<RefCell<HeatmapLayer>>,
+    on_move_end: Option<EventListener<leaflet::Map>>,
+}
+
+struct HeatmapLayer {
+    overlay: CanvasOverlay,
+    /// Web mercator coordinates in the range `[0, 1]` and the weight.
+    projected: Vec<(f64, f64, f64)>,
+    options: HeatmapOptions,
+}
+
+impl HeatmapLayer {
+    fn update_points(&mut self, points: &[(LatLng, f64)]) {
+        self.projected = points
+            .iter()
+            .map(|&(point, weight)| (lng_x(point.lng), lat_y(point.lat), weight))
+            .collect();
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn render(&self) {
+        // The density is calculated per CSS pixel.
+        let Some((ctx, viewport)) = self.overlay.reset(1.0) else {
+            return;
+        };
+        let width = viewport.width as usize;
+        let height = viewport.height as usize;
+        if width == 0 || height == 0 {
+            return;
+        }
+        let margin = self.options.radius;
+        let mut raster = DensityRaster::new(width, height);
+        raster.accumulate(
+            self.projected
+                .iter()
+                .map(|&(x, y, weight)| {
+                    let (x, y) = viewport.to_pixels((x, y));
+                    (x, y, weight)
+                })
+                .filter(|(x, y, _)| {
+                    *x >= -margin
+                        && *y >= -margin
+                        && *x <= viewport.width + margin
+                        && *y <= viewport.height + margin
+                }),
+            &self.options.kernel(),
+        );
+        let max = self.options.max.unwrap_or_else(|| raster.max());
+        let rgba = raster.to_rgba(&self.options.gradient, max, self.options.min_opacity);
+        let image_data = web_sys::ImageData::new_with_u8_clamped_array_and_sh(
+            Clamped(&rgba),
+            width as u32,
+            height as u32,
+        )
+        .unwrap_throw();
+        ctx.put_image_data(&image_data, 0.0, 0.0).unwrap_throw();
+    }
+}
+
+impl<State, Action> View<State, Action, MapCtx, DynMessage> for Heatmap {
+    type Element = MapChildElement;
+
+    type ViewState = HeatmapViewState;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        let mut layer = HeatmapLayer {
+            overlay: CanvasOverlay::new(ctx.map()),
+            projected: Vec::new(),
+            options: self.options.clone(),
+        };
+        layer.update_points(&self.points);
+        layer.render();
+        let layer = Rc::new(RefCell::new(layer));
+        // Leaflet also fires `moveend` after zooming and resizing,
+        // so the heatmap is rendered only once per view change.
+        let on_move_end = {
+            let layer = Rc::clone(&layer);
+            EventListener::new(ctx.map(), "moveend", move |_: leaflet::Event| {
+                layer.borrow().render();
+            })
+        };
+        let view_state = HeatmapViewState {
+            layer,
+            on_move_end: Some(on_move_end),
+        };
+        (MapChildElement::Group, view_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        _: &mut MapCtx,
+        _: Mut<Self::Element>,
+    ) {
+        if prev.points == self.points && prev.options == self.options {
+            return;
+        }
+        let mut layer = view_state.layer.borrow_mut();
+        if prev.points != self.points {
+            layer.update_points(&self.points);
+        }
+        layer.options = self.options.clone();
+        layer.render();
+    }
+
+    fn teardown(&self, view_state: &mut Self::ViewState, _: &mut MapCtx, _: Mut<Self::Element>) {
+        view_state.on_move_end = None;
+        view_state.layer.borrow().overlay.remove();
+    }
+
+    fn message(
+        &self,
+        _: &mut Self::ViewState,
+        _: &[ViewId],
+        message: DynMessage,
+        _: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        MessageResult::Stale(message)
+    }
+}
@@ -0,0 +1,191 @@
+use std::{collections::HashMap, hash::Hash};
+
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker as _},
+    DynMessage,
+};
+
+use crate::{LeafletBackend, MapBackend, MapChildElement, MapCtx};
+
+/// Create map children with a stable identity.
+///
+/// In contrast to a `Vec` of views, which is diffed by index,
+/// the children are diffed by the key of each item.
+/// Inserting, removing or reordering items therefore
+/// only touches the Leaflet layers of the affected items.
+pub fn keyed<I, K, V>(
+    items: I,
+    key: impl Fn(&I::Item) -> K,
+    view: impl Fn(I::Item) -> V,
+) -> Keyed<K, V>
+where
+    I: IntoIterator,
+    K: Hash + Eq,
+{
+    let children = items
+        .into_iter()
+        .map(|item| (key(&item), view(item)))
+        .collect();
+    Keyed { children }
+}
+
+pub struct Keyed<K, V> {
+    children: Vec<(K, V)>,
+}
+
+impl<K, V> Keyed<K, V> {
+    /// The keys and views of the children.
+    pub(crate) fn children(&self) -> &[(K, V)] {
+        &self.children
+    }
+}
+
+impl<K, V> ViewMarker for Keyed<K, V> {}
+
+pub struct KeyedViewState<K, S, B: MapBackend = LeafletBackend> {
+    entries: HashMap<K, KeyedEntry<S, B>>,
+    keys: HashMap<u64, K>,
+    next_id: u64,
+}
+
+struct KeyedEntry<S, B: MapBackend> {
+    id: ViewId,
+    /// Position of the child within the most recent [`Keyed::children`].
+    idx: usize,
+    element: MapChildElement<B>,
+    state: S,
+}
+
+impl<K, S, B: MapBackend> KeyedViewState<K, S, B>
+where
+    K: Hash + Eq + Clone,
+{
+    fn next_id(&mut self, key: &K) -> ViewId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.keys.insert(id, key.clone());
+        ViewId::new(id)
+    }
+}
+
+impl<State, Action, K, V, B> View<State, Action, MapCtx<B>, DynMessage> for Keyed<K, V>
+where
+    State: 'static,
+    Action: 'static,
+    K: Hash + Eq + Clone + 'static,
+    V: View<State, Action, MapCtx<B>, DynMessage, Element = MapChildElement<B>>,
+    B: MapBackend,
+{
+    type Element = MapChildElement<B>;
+
+    type ViewState = KeyedViewState<K, V::ViewState, B>;
+
+    fn build(&self, ctx: &mut MapCtx<B>) -> (Self::Element, Self::ViewState) {
+        let mut view_state = KeyedViewState {
+            entries: HashMap::with_capacity(self.children.len()),
+            keys: HashMap::with_capacity(self.children.len()),
+            next_id: 0,
+        };
+        for (idx, (key, child)) in self.children.iter().enumerate() {
+            let id = view_state.next_id(key);
+            let (element, state) = ctx.with_id(id, |ctx| child.build(ctx));
+            let entry = KeyedEntry {
+                id,
+                idx,
+                element,
+                state,
+            };
+            if let Some(mut duplicate) = view_state.entries.insert(key.clone(), entry) {
+                log::warn!("Duplicate key in keyed map children");
+                let (_, duplicate_child) = &self.children[duplicate.idx];
+                ctx.with_id(duplicate.id, |ctx| {
+                    duplicate_child.teardown(&mut duplicate.state, ctx, &mut duplicate.element);
+                });
+                view_state.keys.remove(&duplicate.id.routing_id());
+            }
+        }
+        (MapChildElement::Group, view_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx<B>,
+        _: Mut<Self::Element>,
+    ) {
+        let mut entries = HashMap::with_capacity(self.children.len());
+        for (idx, (key, child)) in self.children.iter().enumerate() {
+            let entry = if let Some(mut entry) = view_state.entries.remove(key) {
+                let (_, prev_child) = &prev.children[entry.idx];
+                ctx.with_id(entry.id, |ctx| {
+                    child.rebuild(prev_child, &mut entry.state, ctx, &mut entry.element);
+                });
+                entry.idx = idx;
+                entry
+            } else {
+                let id = view_state.next_id(key);
+                let (element, state) = ctx.with_id(id, |ctx| child.build(ctx));
+                KeyedEntry {
+                    id,
+                    idx,
+                    element,
+                    state,
+                }
+            };
+            if let Some(mut duplicate) = entries.insert(key.clone(), entry) {
+                log::warn!("Duplicate key in keyed map children");
+                let (_, duplicate_child) = &self.children[duplicate.idx];
+                ctx.with_id(duplicate.id, |ctx| {
+                    duplicate_child.teardown(&mut duplicate.state, ctx, &mut duplicate.element);
+                });
+                view_state.keys.remove(&duplicate.id.routing_id());
+            }
+        }
+        // Everything that is left has been removed.
+        for (_, mut entry) in view_state.entries.drain() {
+            let (_, prev_child) = &prev.children[entry.idx];
+            ctx.with_id(entry.id, |ctx| {
+                prev_child.teardown(&mut entry.state, ctx, &mut entry.element);
+            });
+            view_state.keys.remove(&entry.id.routing_id());
+        }
+        view_state.entries = entries;
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx<B>,
+        _: Mut<Self::Element>,
+    ) {
+        for (_, mut entry) in view_state.entries.drain() {
+            let (_, child) = &self.children[entry.idx];
+            ctx.with_id(entry.id, |ctx| {
+                child.teardown(&mut entry.state, ctx, &mut entry.element);
+            });
+        }
+        view_state.keys.clear();
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        let Some((first, rest)) = id_path.split_first() else {
+            return MessageResult::Stale(message);
+        };
+        let Some(entry) = view_state
+            .keys
+            .get(&first.routing_id())
+            .and_then(|key| view_state.entries.get_mut(key))
+        else {
+            return MessageResult::Stale(message);
+        };
+        let (_, child) = &self.children[entry.idx];
+        child.message(&mut entry.state, rest, message, app_state)
+    }
+}
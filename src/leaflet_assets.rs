@@ -0,0 +1,209 @@
+//! Load the Leaflet script and stylesheet at runtime.
+//!
+//! By default the assets are loaded from a CDN and checked against
+//! their [subresource integrity](https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity)
+//! hashes. With the `embed-leaflet` feature they are embedded into the binary instead,
+//! so that the app works offline (the marker images are not embedded though,
+//! they are expected in the `images` directory next to the page).
+//! The build script reads them from `assets/leaflet` (see `assets/leaflet/fetch.sh`)
+//! or from the directory in `LEAFLET_ASSETS_DIR` and verifies them.
+//!
+//! The [`map`](crate::map) loads the assets on its own if the page doesn't include them.
+
+use std::{cell::RefCell, fmt};
+
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    js_sys::{self, Promise, Reflect},
+    wasm_bindgen::{JsValue, UnwrapThrowExt as _},
+};
+
+/// The version of Leaflet that the views are implemented for.
+pub const LEAFLET_VERSION: &str = "1.9.4";
+
+/// The script on the CDN.
+pub const SCRIPT_URL: &str = "https://unpkg.com/leaflet@1.9.4/dist/leaflet.js";
+pub const SCRIPT_INTEGRITY: &str = "sha256-20nQCchB9co0qIjJZRGuk2/Z9VM+kNiyxNV1lvTlZBo=";
+
+/// The stylesheet on the CDN.
+pub const STYLESHEET_URL: &str = "https://unpkg.com/leaflet@1.9.4/dist/leaflet.css";
+pub const STYLESHEET_INTEGRITY: &str = "sha256-p4NxAoJBhIIN+hmNHrzRCf9tD/miZyoHS5obTRR9BMY=";
+
+#[cfg(feature = "embed-leaflet")]
+const EMBEDDED_SCRIPT: &str = include_str!(concat!(env!("OUT_DIR"), "/leaflet.js"));
+#[cfg(feature = "embed-leaflet")]
+const EMBEDDED_STYLESHEET: &str = include_str!(concat!(env!("OUT_DIR"), "/leaflet.css"));
+
+/// The rejection reasons of the loading promise.
+const SCRIPT: &str = "script";
+const STYLESHEET: &str = "stylesheet";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadError {
+    Script,
+    Stylesheet,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Script => f.write_str("the Leaflet script could not be loaded"),
+            Self::Stylesheet => f.write_str("the Leaflet stylesheet could not be loaded"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+thread_local! {
+    /// The loading that is shared by all callers of [`load`].
+    static LOADING: RefCell<Option<Promise>> = const { RefCell::new(None) };
+}
+
+/// Check whether the Leaflet script and stylesheet have been loaded.
+#[must_use]
+pub fn is_loaded() -> bool {
+    is_script_loaded() && is_stylesheet_loaded()
+}
+
+/// Check whether the Leaflet script has been loaded (i.e. `L` is defined).
+#[must_use]
+pub fn is_script_loaded() -> bool {
+    Reflect::has(&js_sys::global(), &"L".into()).unwrap_or(false)
+}
+
+/// Check whether the Leaflet stylesheet has been loaded
+/// (i.e. the panes of a map would be positioned absolutely).
+#[must_use]
+pub fn is_stylesheet_loaded() -> bool {
+    let document = xilem_web::document();
+    let Some(body) = document.body() else {
+        return false;
+    };
+    let probe = document.create_element("div").unwrap_throw();
+    probe.set_class_name("leaflet-pane");
+    body.append_child(&probe).unwrap_throw();
+    let position = web_sys::window()
+        .unwrap_throw()
+        .get_computed_style(&probe)
+        .ok()
+        .flatten()
+        .and_then(|style| style.get_property_value("position").ok());
+    probe.remove();
+    position.as_deref() == Some("absolute")
+}
+
+/// Add the Leaflet script and stylesheet to the document (once)
+/// and wait until they have been loaded.
+///
+/// Nothing is added for an asset that has already been loaded, e.g. by the page.
+/// After an error, the elements that failed are removed
+/// and the next call tries again.
+pub async fn load() -> Result<(), LoadError> {
+    if is_loaded() {
+        return Ok(());
+    }
+    let loading = LOADING.with_borrow_mut(|loading| loading.get_or_insert_with(inject).clone());
+    JsFuture::from(loading).await.map(|_| ()).map_err(|reason| {
+        LOADING.with_borrow_mut(|loading| *loading = None);
+        if reason.as_string().as_deref() == Some(STYLESHEET) {
+            LoadError::Stylesheet
+        } else {
+            LoadError::Script
+        }
+    })
+}
+
+fn inject() -> Promise {
+    let loading = js_sys::Array::new();
+    if !is_stylesheet_loaded() {
+        loading.push(&inject_stylesheet());
+    }
+    if !is_script_loaded() {
+        loading.push(&inject_script());
+    }
+    Promise::all(&loading)
+}
+
+/// Add the element to the head and remove it again if it fails to load.
+#[cfg(not(feature = "embed-leaflet"))]
+fn append_to_head(element: web_sys::HtmlElement, error: &'static str) -> Promise {
+    use wasm_bindgen_futures::future_to_promise;
+
+    let head = xilem_web::document()
+        .head()
+        .expect_throw("document without head");
+    let loaded = Promise::new(&mut |resolve, reject| {
+        element.set_onload(Some(&resolve));
+        element.set_onerror(Some(&reject.bind1(&JsValue::NULL, &error.into())));
+    });
+    head.append_child(&element).unwrap_throw();
+    future_to_promise(async move {
+        // A retry adds a new element.
+        JsFuture::from(loaded)
+            .await
+            .inspect_err(|_| element.remove())
+    })
+}
+
+#[cfg(not(feature = "embed-leaflet"))]
+fn inject_stylesheet() -> Promise {
+    use web_sys::wasm_bindgen::JsCast as _;
+
+    let link: web_sys::HtmlLinkElement = xilem_web::document()
+        .create_element("link")
+        .unwrap_throw()
+        .unchecked_into();
+    link.set_rel("stylesheet");
+    link.set_href(STYLESHEET_URL);
+    link.set_integrity(STYLESHEET_INTEGRITY);
+    link.set_cross_origin(Some("anonymous"));
+    append_to_head(link.into(), STYLESHEET)
+}
+
+#[cfg(not(feature = "embed-leaflet"))]
+fn inject_script() -> Promise {
+    use web_sys::wasm_bindgen::JsCast as _;
+
+    let script: web_sys::HtmlScriptElement = xilem_web::document()
+        .create_element("script")
+        .unwrap_throw()
+        .unchecked_into();
+    script.set_src(SCRIPT_URL);
+    script.set_integrity(SCRIPT_INTEGRITY);
+    script.set_cross_origin(Some("anonymous"));
+    append_to_head(script.into(), SCRIPT)
+}
+
+#[cfg(feature = "embed-leaflet")]
+fn inject_stylesheet() -> Promise {
+    let style = xilem_web::document().create_element("style").unwrap_throw();
+    style.set_text_content(Some(EMBEDDED_STYLESHEET));
+    append_inline(&style, is_stylesheet_loaded, STYLESHEET)
+}
+
+#[cfg(feature = "embed-leaflet")]
+fn inject_script() -> Promise {
+    // Inline scripts are executed when they are inserted
+    // (unless they are blocked by a content security policy).
+    let script = xilem_web::document()
+        .create_element("script")
+        .unwrap_throw();
+    script.set_text_content(Some(EMBEDDED_SCRIPT));
+    append_inline(&script, is_script_loaded, SCRIPT)
+}
+
+/// Add the inline element to the head and remove it again if it has not been applied.
+#[cfg(feature = "embed-leaflet")]
+fn append_inline(element: &web_sys::Element, is_loaded: fn() -> bool, error: &str) -> Promise {
+    let head = xilem_web::document()
+        .head()
+        .expect_throw("document without head");
+    head.append_child(element).unwrap_throw();
+    if is_loaded() {
+        Promise::resolve(&JsValue::UNDEFINED)
+    } else {
+        element.remove();
+        Promise::reject(&error.into())
+    }
+}
@@ -0,0 +1,63 @@
+//! Counters of live Leaflet resources to detect leaks,
+//! e.g. to verify in tests that a map is released after its teardown.
+//!
+//! The counters are only available in debug builds.
+
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(debug_assertions)]
+static LIVE_MAPS: AtomicUsize = AtomicUsize::new(0);
+#[cfg(debug_assertions)]
+static LIVE_LISTENERS: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of maps that have been built and not been torn down yet.
+#[cfg(debug_assertions)]
+#[must_use]
+pub fn live_maps() -> usize {
+    LIVE_MAPS.load(Ordering::Relaxed)
+}
+
+/// The number of event listeners and other closures (of maps and layers)
+/// that have not been released yet.
+#[cfg(debug_assertions)]
+#[must_use]
+pub fn live_listeners() -> usize {
+    LIVE_LISTENERS.load(Ordering::Relaxed)
+}
+
+/// Counts a live resource until it is dropped (only in debug builds,
+/// otherwise it is empty).
+pub(crate) struct LiveToken {
+    #[cfg(debug_assertions)]
+    counter: &'static AtomicUsize,
+}
+
+impl LiveToken {
+    pub(crate) fn map() -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            counter: count(&LIVE_MAPS),
+        }
+    }
+
+    pub(crate) fn listener() -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            counter: count(&LIVE_LISTENERS),
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+fn count(counter: &'static AtomicUsize) -> &'static AtomicUsize {
+    counter.fetch_add(1, Ordering::Relaxed);
+    counter
+}
+
+#[cfg(debug_assertions)]
+impl Drop for LiveToken {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::Relaxed);
+    }
+}
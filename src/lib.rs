@@ -1,7 +1,39 @@
 #![doc = include_str!("../README.md")]
 
+mod bounds_loader;
+mod canvas_overlay;
+mod clustering;
+mod custom_layer;
+mod density;
+mod draw_control;
+mod either;
+mod geometry;
+mod grid_layer;
+mod heatmap;
+mod keyed;
+pub mod leaflet_assets;
+pub mod leak_check;
 mod map;
 mod marker;
+mod marker_cluster;
+pub mod measure;
+mod measure_tool;
+mod point_cloud;
+mod polyline;
+pub mod projection;
+mod selection_tool;
+mod shape;
+mod spatial_index;
+// Only public for the benchmarks.
+#[doc(hidden)]
+pub mod splice;
 mod tile_layer;
+mod viewport_culled;
 
-pub use self::{map::*, marker::*, tile_layer::*};
+pub use self::projection::{TileCoords, TileRange};
+pub use self::{
+    bounds_loader::*, clustering::*, custom_layer::*, density::*, draw_control::*, either::*,
+    geometry::*, grid_layer::*, heatmap::*, keyed::*, map::*, marker::*, marker_cluster::*,
+    measure_tool::*, point_cloud::*, polyline::*, selection_tool::*, shape::*, spatial_index::*,
+    tile_layer::*, viewport_culled::*,
+};
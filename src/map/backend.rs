@@ -0,0 +1,163 @@
+use std::fmt;
+
+use crate::LatLng;
+
+/// The operations of the views on the map.
+///
+/// The views are built with the [`LeafletBackend`] inside a [`map`](crate::map).
+/// Other backends (see [`HeadlessBackend`]) allow to build them without a browser.
+/// Only these views are generic over the backend:
+///
+/// - [`marker`](crate::marker) and [`tile_layer`](crate::tile_layer)
+/// - [`keyed`](crate::keyed), [`Either`](crate::Either) and boxed [`AnyMapView`](crate::AnyMapView)
+///   with children of these views
+///
+/// All other views (e.g. the event handlers) need the browser
+/// and are only implemented for the [`LeafletBackend`].
+pub trait MapBackend: fmt::Debug + 'static {
+    type Marker: fmt::Debug;
+    type TileLayer: fmt::Debug;
+
+    fn add_marker(&mut self, position: LatLng) -> Self::Marker;
+
+    fn set_marker_position(&mut self, marker: &Self::Marker, position: LatLng);
+
+    fn remove_marker(&mut self, marker: &Self::Marker);
+
+    fn add_tile_layer(&mut self, url_template: &str) -> Self::TileLayer;
+
+    fn remove_tile_layer(&mut self, layer: &Self::TileLayer);
+}
+
+/// A backend that doesn't need a browser,
+/// so that the views can be built with [`MapCtx::with_backend`](crate::MapCtx::with_backend).
+pub trait HeadlessBackend: MapBackend {}
+
+/// The default backend that creates Leaflet layers.
+#[derive(Debug, Clone)]
+pub struct LeafletBackend {
+    map: leaflet::Map,
+}
+
+impl LeafletBackend {
+    #[must_use]
+    pub const fn new(map: leaflet::Map) -> Self {
+        Self { map }
+    }
+
+    #[must_use]
+    pub const fn map(&self) -> &leaflet::Map {
+        &self.map
+    }
+}
+
+impl MapBackend for LeafletBackend {
+    type Marker = leaflet::Marker;
+    type TileLayer = leaflet::TileLayer;
+
+    fn add_marker(&mut self, position: LatLng) -> Self::Marker {
+        let marker = leaflet::Marker::new(&position.into());
+        marker.add_to(&self.map);
+        marker
+    }
+
+    fn set_marker_position(&mut self, marker: &Self::Marker, position: LatLng) {
+        marker.set_lat_lng(&position.into());
+    }
+
+    fn remove_marker(&mut self, marker: &Self::Marker) {
+        marker.remove();
+    }
+
+    fn add_tile_layer(&mut self, url_template: &str) -> Self::TileLayer {
+        let tile_layer = leaflet::TileLayer::new(url_template);
+        self.map.add_layer(&tile_layer);
+        tile_layer
+    }
+
+    fn remove_tile_layer(&mut self, layer: &Self::TileLayer) {
+        layer.remove();
+    }
+}
+
+/// Identifies a layer of the [`RecordingBackend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LayerId(pub u64);
+
+/// An operation that has been recorded by the [`RecordingBackend`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackendOp {
+    AddMarker { id: LayerId, position: LatLng },
+    SetMarkerPosition { id: LayerId, position: LatLng },
+    AddTileLayer { id: LayerId, url_template: String },
+    RemoveLayer(LayerId),
+}
+
+/// An in-memory backend that only records the operations,
+/// e.g. to test the views natively.
+#[derive(Debug, Default)]
+pub struct RecordingBackend {
+    ops: Vec<BackendOp>,
+    next_id: u64,
+}
+
+impl RecordingBackend {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All operations in the order they have been issued.
+    #[must_use]
+    pub fn ops(&self) -> &[BackendOp] {
+        &self.ops
+    }
+
+    /// Take the operations that have been issued since the last call.
+    pub fn take_ops(&mut self) -> Vec<BackendOp> {
+        std::mem::take(&mut self.ops)
+    }
+
+    fn next_id(&mut self) -> LayerId {
+        let id = LayerId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+}
+
+impl HeadlessBackend for RecordingBackend {}
+
+impl MapBackend for RecordingBackend {
+    type Marker = LayerId;
+    type TileLayer = LayerId;
+
+    fn add_marker(&mut self, position: LatLng) -> Self::Marker {
+        let id = self.next_id();
+        self.ops.push(BackendOp::AddMarker { id, position });
+        id
+    }
+
+    fn set_marker_position(&mut self, marker: &Self::Marker, position: LatLng) {
+        self.ops.push(BackendOp::SetMarkerPosition {
+            id: *marker,
+            position,
+        });
+    }
+
+    fn remove_marker(&mut self, marker: &Self::Marker) {
+        self.ops.push(BackendOp::RemoveLayer(*marker));
+    }
+
+    fn add_tile_layer(&mut self, url_template: &str) -> Self::TileLayer {
+        let id = self.next_id();
+        self.ops.push(BackendOp::AddTileLayer {
+            id,
+            url_template: url_template.to_string(),
+        });
+        id
+    }
+
+    fn remove_tile_layer(&mut self, layer: &Self::TileLayer) {
+        self.ops.push(BackendOp::RemoveLayer(*layer));
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+
+use web_sys::{
+    js_sys::{Array, Function, Object, Reflect},
+    wasm_bindgen::{JsCast as _, JsValue, UnwrapThrowExt as _},
+};
+
+use crate::{Bounds, Point};
+
+/// The coordinate reference system of a map.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Crs {
+    /// Spherical mercator projection (used by most tile providers).
+    #[default]
+    EPSG3857,
+    /// Equirectangular projection.
+    EPSG4326,
+    /// Elliptical mercator projection.
+    EPSG3395,
+    /// Maps the coordinates directly to pixels (`lat` is `y`, `lng` is `x`).
+    ///
+    /// This is useful for non-geographic maps like floor plans or game maps.
+    Simple,
+    /// A custom projection (requires the [Proj4Leaflet](https://github.com/kartena/Proj4Leaflet) plugin).
+    Proj4(Proj4Crs),
+}
+
+impl Crs {
+    /// Whether the coordinates are geographic,
+    /// i.e. every CRS except [`Crs::Simple`].
+    #[must_use]
+    pub const fn is_geographic(&self) -> bool {
+        !matches!(self, Self::Simple)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrsError {
+    /// Leaflet does not define the CRS (e.g. because it has not been loaded).
+    Unsupported(&'static str),
+    /// The Proj4Leaflet plugin has not been loaded.
+    Proj4Missing,
+    /// Proj4Leaflet was unable to create the CRS with the given code.
+    InvalidProj4(String),
+}
+
+impl fmt::Display for CrsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(name) => write!(f, "the CRS L.CRS.{name} is not defined"),
+            Self::Proj4Missing => f.write_str("the Proj4Leaflet plugin has not been loaded"),
+            Self::InvalidProj4(code) => write!(f, "the CRS {code} could not be created"),
+        }
+    }
+}
+
+impl std::error::Error for CrsError {}
+
+/// A custom CRS defined by a Proj4 string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proj4Crs {
+    /// The code of the CRS (e.g. `EPSG:3006`).
+    pub code: String,
+    /// The Proj4 definition (e.g. `+proj=utm +zone=33 +ellps=GRS80 +units=m +no_defs`).
+    pub definition: String,
+    /// Projected units per pixel for each zoom level.
+    pub resolutions: Vec<f64>,
+    /// The origin of the tile grid in projected units.
+    pub origin: Option<Point>,
+    /// The bounds of the CRS in projected units.
+    pub bounds: Option<Bounds>,
+}
+
+impl Proj4Crs {
+    #[must_use]
+    pub fn new(code: impl Into<String>, definition: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            definition: definition.into(),
+            resolutions: Vec::new(),
+            origin: None,
+            bounds: None,
+        }
+    }
+
+    #[must_use]
+    pub fn resolutions(mut self, resolutions: impl Into<Vec<f64>>) -> Self {
+        self.resolutions = resolutions.into();
+        self
+    }
+
+    #[must_use]
+    pub fn origin(mut self, origin: impl Into<Point>) -> Self {
+        self.origin = Some(origin.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn bounds(mut self, bounds: Bounds) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    fn to_js(&self) -> Result<JsValue, CrsError> {
+        let constructor = global_property(&["L", "Proj", "CRS"]).ok_or(CrsError::Proj4Missing)?;
+        let options = Object::new();
+        if !self.resolutions.is_empty() {
+            let resolutions: Array = self.resolutions.iter().map(|&r| JsValue::from(r)).collect();
+            Reflect::set(&options, &"resolutions".into(), &resolutions).unwrap_throw();
+        }
+        if let Some(origin) = self.origin {
+            let origin = Array::of2(&origin.x.into(), &origin.y.into());
+            Reflect::set(&options, &"origin".into(), &origin).unwrap_throw();
+        }
+        if let Some(bounds) = self.bounds {
+            let bounds = leaflet::Bounds::from(bounds);
+            Reflect::set(&options, &"bounds".into(), &bounds).unwrap_throw();
+        }
+        let args = Array::of3(
+            &self.code.as_str().into(),
+            &self.definition.as_str().into(),
+            &options,
+        );
+        Reflect::construct(constructor.unchecked_ref::<Function>(), &args)
+            .map(JsValue::from)
+            .map_err(|_| CrsError::InvalidProj4(self.code.clone()))
+    }
+}
+
+impl Crs {
+    fn to_js(&self) -> Result<JsValue, CrsError> {
+        let name = match self {
+            Self::EPSG3857 => "EPSG3857",
+            Self::EPSG4326 => "EPSG4326",
+            Self::EPSG3395 => "EPSG3395",
+            Self::Simple => "Simple",
+            Self::Proj4(crs) => return crs.to_js(),
+        };
+        global_property(&["L", "CRS", name]).ok_or(CrsError::Unsupported(name))
+    }
+
+    /// Set the CRS of the map options.
+    pub(crate) fn apply_to_options(&self, options: &leaflet::MapOptions) -> Result<(), CrsError> {
+        Reflect::set(options, &"crs".into(), &self.to_js()?).unwrap_throw();
+        Ok(())
+    }
+
+    /// Replace the CRS of an existing map and keep the current view.
+    ///
+    /// The map keeps its CRS if the new one can't be created.
+    pub(crate) fn apply_to_map(&self, map: &leaflet::Map) -> Result<(), CrsError> {
+        let crs = self.to_js()?;
+        let options = Reflect::get(map, &"options".into()).unwrap_throw();
+        Reflect::set(&options, &"crs".into(), &crs).unwrap_throw();
+        if !super::is_loaded(map) {
+            return Ok(());
+        }
+        // The pixel origin depends on the CRS, so the view has to be reset
+        // (instead of being animated to the same center and zoom).
+        let reset = Object::new();
+        Reflect::set(&reset, &"reset".into(), &true.into()).unwrap_throw();
+        let set_view: Function = Reflect::get(map, &"setView".into())
+            .unwrap_throw()
+            .unchecked_into();
+        set_view
+            .call3(map, &map.get_center(), &map.get_zoom().into(), &reset)
+            .unwrap_throw();
+        Ok(())
+    }
+}
+
+fn global_property(path: &[&str]) -> Option<JsValue> {
+    path.iter()
+        .try_fold(JsValue::from(web_sys::js_sys::global()), |object, key| {
+            Reflect::get(&object, &(*key).into())
+                .ok()
+                .filter(|value| !value.is_undefined())
+        })
+}
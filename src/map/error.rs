@@ -0,0 +1,48 @@
+use std::fmt;
+
+use super::CrsError;
+use crate::{leaflet_assets::LoadError, InvalidLatLng};
+
+/// An error that prevents the map from being displayed as described by the view
+/// (see [`Map::on_error`](super::Map::on_error)).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapError {
+    /// The CRS could not be created, so the map uses the previous (or default) CRS.
+    Crs(CrsError),
+    /// The Leaflet assets could not be loaded, so the map stays empty.
+    Load(LoadError),
+    /// The center is not valid for the geographic CRS, so the map keeps its view.
+    InvalidCenter(InvalidLatLng),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Crs(error) => write!(f, "invalid CRS: {error}"),
+            Self::Load(error) => write!(f, "missing Leaflet: {error}"),
+            Self::InvalidCenter(error) => write!(f, "invalid center: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for MapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Crs(error) => Some(error),
+            Self::Load(error) => Some(error),
+            Self::InvalidCenter(error) => Some(error),
+        }
+    }
+}
+
+impl From<CrsError> for MapError {
+    fn from(error: CrsError) -> Self {
+        Self::Crs(error)
+    }
+}
+
+impl From<LoadError> for MapError {
+    fn from(error: LoadError) -> Self {
+        Self::Load(error)
+    }
+}
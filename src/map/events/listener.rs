@@ -0,0 +1,110 @@
+use leaflet::Evented;
+use web_sys::{
+    wasm_bindgen::{closure::Closure, JsCast, JsValue, UnwrapThrowExt as _},
+    EventTarget,
+};
+
+use crate::leak_check::LiveToken;
+
+/// A Leaflet event listener that is removed when it is dropped.
+pub(crate) struct EventListener<T: Evented> {
+    target: T,
+    event_type: &'static str,
+    closure: Closure<dyn Fn(JsValue)>,
+    _live: LiveToken,
+}
+
+impl<T: Evented> EventListener<T> {
+    pub(crate) fn new<E>(
+        target: &T,
+        event_type: &'static str,
+        callback: impl Fn(E) + 'static,
+    ) -> Self
+    where
+        E: JsCast,
+    {
+        let closure = Closure::<dyn Fn(JsValue)>::new(move |ev: JsValue| {
+            callback(ev.unchecked_into());
+        });
+        target.on(event_type, closure.as_ref());
+        Self {
+            target: target.clone(),
+            event_type,
+            closure,
+            _live: LiveToken::listener(),
+        }
+    }
+}
+
+impl<T: Evented> Drop for EventListener<T> {
+    fn drop(&mut self) {
+        self.target.off(self.event_type, self.closure.as_ref());
+    }
+}
+
+/// A DOM event listener (e.g. of the document) that is removed when it is dropped.
+pub(crate) struct DomEventListener {
+    target: EventTarget,
+    event_type: &'static str,
+    closure: Closure<dyn Fn(JsValue)>,
+    _live: LiveToken,
+}
+
+impl DomEventListener {
+    pub(crate) fn new<E>(
+        target: &EventTarget,
+        event_type: &'static str,
+        callback: impl Fn(E) + 'static,
+    ) -> Self
+    where
+        E: JsCast,
+    {
+        let closure = Closure::<dyn Fn(JsValue)>::new(move |ev: JsValue| {
+            callback(ev.unchecked_into());
+        });
+        target
+            .add_event_listener_with_callback(event_type, closure.as_ref().unchecked_ref())
+            .unwrap_throw();
+        Self {
+            target: target.clone(),
+            event_type,
+            closure,
+            _live: LiveToken::listener(),
+        }
+    }
+}
+
+impl Drop for DomEventListener {
+    fn drop(&mut self) {
+        self.target
+            .remove_event_listener_with_callback(
+                self.event_type,
+                self.closure.as_ref().unchecked_ref(),
+            )
+            .unwrap_throw();
+    }
+}
+
+/// A closure that is called by Leaflet or the browser but is not an [`EventListener`]
+/// (e.g. an overridden method or an observer callback).
+///
+/// It is counted as a live listener until it is dropped.
+pub(crate) struct TrackedClosure<T: ?Sized> {
+    closure: Closure<T>,
+    _live: LiveToken,
+}
+
+impl<T: ?Sized> TrackedClosure<T> {
+    pub(crate) fn new(closure: Closure<T>) -> Self {
+        Self {
+            closure,
+            _live: LiveToken::listener(),
+        }
+    }
+}
+
+impl<T: ?Sized> AsRef<JsValue> for TrackedClosure<T> {
+    fn as_ref(&self) -> &JsValue {
+        self.closure.as_ref()
+    }
+}
@@ -1,5 +1,9 @@
+mod listener;
 mod on_mouse_click;
 mod on_move_end;
+mod on_ready;
+mod on_resize;
 mod on_zoom_end;
 
-pub use self::{on_mouse_click::*, on_move_end::*, on_zoom_end::*};
+pub(crate) use self::listener::{DomEventListener, EventListener, TrackedClosure};
+pub use self::{on_mouse_click::*, on_move_end::*, on_ready::*, on_resize::*, on_zoom_end::*};
@@ -4,6 +4,7 @@ use xilem_web::{
     DynMessage,
 };
 
+use super::EventListener;
 use crate::{MapChildElement, MapCtx};
 
 pub(crate) const fn on_mouse_click<State, F>(callback: F) -> OnMouseClick<F>
@@ -23,7 +24,7 @@ impl<F> ViewMarker for OnMouseClick<F> {}
 struct ClickMessage(leaflet::MouseEvent);
 
 /// Distinctive ID for better debugging
-const ON_MOUSE_CLICK_ID: ViewId = ViewId::new(23668);
+const ON_MOUSE_CLICK_ID: ViewId = ViewId::new(23679);
 
 impl<State, Action, F> View<State, Action, MapCtx, DynMessage> for OnMouseClick<F>
 where
@@ -32,28 +33,26 @@ where
 {
     type Element = MapChildElement;
 
-    type ViewState = ();
+    type ViewState = Option<EventListener<leaflet::Map>>;
 
     fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
         ctx.with_id(ON_MOUSE_CLICK_ID, |ctx| {
-            let thunk = ctx.dom_ctx.message_thunk();
-            // TODO use add/remove_event_listener, for graceful lifecycle handling
-            ctx.map
-                .on_mouse_click(Box::new(move |ev| thunk.push_message(ClickMessage(ev))));
-            (MapChildElement::Event, ())
+            let thunk = ctx.message_thunk();
+            let listener =
+                EventListener::new(ctx.map(), "click", move |ev: leaflet::MouseEvent| {
+                    thunk.push_message(ClickMessage(ev));
+                });
+            (MapChildElement::Event, Some(listener))
         })
     }
 
-    fn rebuild(&self, _: &Self, _: &mut Self::ViewState, ctx: &mut MapCtx, _: Mut<Self::Element>) {
-        ctx.with_id(ON_MOUSE_CLICK_ID, |_ctx| {
-            // TODO
-        })
+    fn rebuild(&self, _: &Self, _: &mut Self::ViewState, _: &mut MapCtx, _: Mut<Self::Element>) {
+        // The callback is called with the current view.
     }
 
-    fn teardown(&self, _: &mut Self::ViewState, ctx: &mut MapCtx, _: Mut<Self::Element>) {
-        ctx.with_id(ON_MOUSE_CLICK_ID, |_ctx| {
-            // TODO
-        })
+    fn teardown(&self, listener: &mut Self::ViewState, _: &mut MapCtx, _: Mut<Self::Element>) {
+        // The listener is removed when it is dropped.
+        *listener = None;
     }
 
     fn message(
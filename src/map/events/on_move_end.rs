@@ -3,6 +3,7 @@ use xilem_web::{
     DynMessage,
 };
 
+use super::EventListener;
 use crate::{MapChildElement, MapCtx};
 
 pub const fn on_move_end<State, F>(callback: F) -> OnMoveEnd<F>
@@ -32,30 +33,26 @@ where
 {
     type Element = MapChildElement;
 
-    type ViewState = ();
+    type ViewState = Option<EventListener<leaflet::Map>>;
 
     fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
         ctx.with_id(ON_MOVE_END_ID, |ctx| {
-            let thunk = ctx.dom_ctx.message_thunk();
-            let map = ctx.map.clone();
-            // TODO use add/remove_event_listener, for graceful lifecycle handling
-            ctx.map.on_move_end(Box::new(move |ev| {
+            let thunk = ctx.message_thunk();
+            let map = ctx.map().clone();
+            let listener = EventListener::new(ctx.map(), "moveend", move |ev: leaflet::Event| {
                 thunk.enqueue_message(MoveEndMessage(map.clone(), ev));
-            }));
-            (MapChildElement::Event, ())
+            });
+            (MapChildElement::Event, Some(listener))
         })
     }
 
-    fn rebuild(&self, _: &Self, _: &mut Self::ViewState, ctx: &mut MapCtx, _: Mut<Self::Element>) {
-        ctx.with_id(ON_MOVE_END_ID, |_ctx| {
-            // TODO
-        })
+    fn rebuild(&self, _: &Self, _: &mut Self::ViewState, _: &mut MapCtx, _: Mut<Self::Element>) {
+        // The callback is called with the current view.
     }
 
-    fn teardown(&self, _: &mut Self::ViewState, ctx: &mut MapCtx, _: Mut<Self::Element>) {
-        ctx.with_id(ON_MOVE_END_ID, |_ctx| {
-            // TODO
-        })
+    fn teardown(&self, listener: &mut Self::ViewState, _: &mut MapCtx, _: Mut<Self::Element>) {
+        // The listener is removed when it is dropped.
+        *listener = None;
     }
 
     fn message(
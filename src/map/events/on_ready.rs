@@ -0,0 +1,76 @@
+use web_sys::wasm_bindgen::UnwrapThrowExt as _;
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker as _},
+    DynMessage,
+};
+
+use super::EventListener;
+use crate::{map::is_loaded, MapChildElement, MapCtx, MapHandle};
+
+pub const fn on_ready<State, F>(callback: F) -> OnReady<F>
+where
+    F: Fn(&mut State, MapHandle) + 'static,
+{
+    OnReady { callback }
+}
+
+pub struct OnReady<F> {
+    callback: F,
+}
+
+impl<F> ViewMarker for OnReady<F> {}
+
+#[derive(Debug)]
+struct ReadyMessage(MapHandle);
+
+/// Distinctive ID for better debugging
+const ON_READY_ID: ViewId = ViewId::new(23681);
+
+impl<State, Action, F> View<State, Action, MapCtx, DynMessage> for OnReady<F>
+where
+    State: 'static,
+    Action: 'static,
+    F: Fn(&mut State, MapHandle) + 'static,
+{
+    type Element = MapChildElement;
+
+    type ViewState = Option<EventListener<leaflet::Map>>;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        ctx.with_id(ON_READY_ID, |ctx| {
+            let thunk = ctx.message_thunk();
+            let handle = MapHandle::new(ctx.map().clone());
+            if is_loaded(ctx.map()) {
+                thunk.enqueue_message(ReadyMessage(handle));
+                return (MapChildElement::Event, None);
+            }
+            // Leaflet fires `load` when the view is set for the first time.
+            let listener = EventListener::new(ctx.map(), "load", move |_: leaflet::Event| {
+                thunk.enqueue_message(ReadyMessage(handle.clone()));
+            });
+            (MapChildElement::Event, Some(listener))
+        })
+    }
+
+    fn rebuild(&self, _: &Self, _: &mut Self::ViewState, _: &mut MapCtx, _: Mut<Self::Element>) {
+        // The callback is called with the current view.
+    }
+
+    fn teardown(&self, listener: &mut Self::ViewState, _: &mut MapCtx, _: Mut<Self::Element>) {
+        // The listener is removed when it is dropped.
+        *listener = None;
+    }
+
+    fn message(
+        &self,
+        _: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        state: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        debug_assert!(id_path.len() == 1 && id_path[0] == ON_READY_ID);
+        let ReadyMessage(handle) = *message.downcast().unwrap_throw();
+        (self.callback)(state, handle);
+        MessageResult::Nop
+    }
+}
@@ -0,0 +1,76 @@
+use web_sys::{
+    js_sys::Reflect,
+    wasm_bindgen::{JsCast as _, UnwrapThrowExt as _},
+};
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker as _},
+    DynMessage,
+};
+
+use super::EventListener;
+use crate::{MapChildElement, MapCtx, Point};
+
+pub const fn on_resize<State, F>(callback: F) -> OnResize<F>
+where
+    F: Fn(&mut State, Point) + 'static,
+{
+    OnResize { callback }
+}
+
+pub struct OnResize<F> {
+    callback: F,
+}
+
+impl<F> ViewMarker for OnResize<F> {}
+
+#[derive(Debug)]
+struct ResizeMessage(Point);
+
+/// Distinctive ID for better debugging
+const ON_RESIZE_ID: ViewId = ViewId::new(23680);
+
+impl<State, Action, F> View<State, Action, MapCtx, DynMessage> for OnResize<F>
+where
+    State: 'static,
+    Action: 'static,
+    F: Fn(&mut State, Point) + 'static,
+{
+    type Element = MapChildElement;
+
+    type ViewState = Option<EventListener<leaflet::Map>>;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        ctx.with_id(ON_RESIZE_ID, |ctx| {
+            let thunk = ctx.message_thunk();
+            let listener = EventListener::new(ctx.map(), "resize", move |ev: leaflet::Event| {
+                let new_size: leaflet::Point = Reflect::get(&ev, &"newSize".into())
+                    .unwrap_throw()
+                    .unchecked_into();
+                thunk.enqueue_message(ResizeMessage(new_size.into()));
+            });
+            (MapChildElement::Event, Some(listener))
+        })
+    }
+
+    fn rebuild(&self, _: &Self, _: &mut Self::ViewState, _: &mut MapCtx, _: Mut<Self::Element>) {
+        // The callback is called with the current view.
+    }
+
+    fn teardown(&self, listener: &mut Self::ViewState, _: &mut MapCtx, _: Mut<Self::Element>) {
+        // The listener is removed when it is dropped.
+        *listener = None;
+    }
+
+    fn message(
+        &self,
+        _: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        state: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        debug_assert!(id_path.len() == 1 && id_path[0] == ON_RESIZE_ID);
+        let ResizeMessage(size) = *message.downcast().unwrap_throw();
+        (self.callback)(state, size);
+        MessageResult::Nop
+    }
+}
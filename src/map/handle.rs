@@ -0,0 +1,35 @@
+use crate::LatLng;
+
+/// A handle to the Leaflet map for everything
+/// that is not covered by the views (see [`Map::on_ready`](super::Map::on_ready)).
+///
+/// It can be stored in the state and used e.g. in event handlers or async tasks.
+/// After the map view has been torn down, the map has been removed
+/// and the handle must no longer be used.
+#[derive(Debug, Clone)]
+pub struct MapHandle {
+    map: leaflet::Map,
+}
+
+impl MapHandle {
+    pub(crate) const fn new(map: leaflet::Map) -> Self {
+        Self { map }
+    }
+
+    /// The underlying Leaflet map, e.g. to call methods of plugins.
+    #[must_use]
+    pub const fn map(&self) -> &leaflet::Map {
+        &self.map
+    }
+
+    /// The container element of the map.
+    #[must_use]
+    pub fn container(&self) -> web_sys::HtmlElement {
+        self.map.get_container()
+    }
+
+    /// Move the map to a new center and zoom level.
+    pub fn set_view(&self, center: impl Into<LatLng>, zoom: f64) {
+        self.map.set_view(&center.into().into(), zoom);
+    }
+}
@@ -1,45 +1,120 @@
 use std::marker::PhantomData;
 
 use wasm_bindgen_futures::spawn_local;
+use web_sys::{
+    js_sys::{Function, Reflect},
+    wasm_bindgen::{JsCast as _, JsValue, UnwrapThrowExt as _},
+};
 use xilem_web::{
     core::{
-        frozen, AppendVec, ElementSplice, MessageResult, Mut, SuperElement, View, ViewElement,
-        ViewId, ViewMarker, ViewPathTracker, ViewSequence,
+        frozen, AnyElement, AnyView, AppendVec, ElementSplice, MessageResult, Mut, SuperElement,
+        View, ViewElement, ViewId, ViewMarker, ViewPathTracker, ViewSequence,
     },
     elements::html,
-    interfaces::{Element, HtmlElement},
+    interfaces::{Element, HtmlDivElement, HtmlElement},
     modifiers::style,
-    DynMessage, ViewCtx,
+    DynMessage, MessageThunk, ViewCtx,
 };
 
+mod backend;
+mod crs;
+mod error;
 mod events;
-pub use self::events::*;
+mod handle;
+mod mount;
+
+use crate::{leaflet_assets, leak_check::LiveToken, splice::VecSplice, LatLng, Point};
+
+use self::mount::Mount;
+pub use self::{
+    backend::*, crs::*, error::MapError, events::*, handle::MapHandle, mount::ResizeOptions,
+};
 
-pub struct MapCtx {
-    dom_ctx: ViewCtx,
-    map: leaflet::Map,
+/// The context of the map children.
+///
+/// A `MapCtx<LeafletBackend>` is only created by the [`map`], so it always has a DOM context.
+/// Contexts of other backends are created with [`MapCtx::with_backend`] and have none.
+pub struct MapCtx<B: MapBackend = LeafletBackend> {
+    backend: B,
+    /// `None` if the views are built with a [`HeadlessBackend`].
+    dom_ctx: Option<ViewCtx>,
+    /// The view path if there is no DOM context.
+    id_path: Vec<ViewId>,
 }
 
 impl MapCtx {
     fn new(dom_ctx: ViewCtx, map: leaflet::Map) -> Self {
-        Self { dom_ctx, map }
+        Self {
+            backend: LeafletBackend::new(map),
+            dom_ctx: Some(dom_ctx),
+            id_path: Vec::new(),
+        }
     }
+
+    fn into_dom_ctx(self) -> ViewCtx {
+        self.dom_ctx.expect_throw("map context without DOM context")
+    }
+
     pub const fn map(&self) -> &leaflet::Map {
-        &self.map
+        self.backend.map()
+    }
+
+    /// Create a thunk to send messages to the current view (see [`ViewPathTracker::with_id`]).
+    pub fn message_thunk(&self) -> MessageThunk {
+        // Only contexts of a `HeadlessBackend` are created without a DOM context.
+        self.dom_ctx
+            .as_ref()
+            .expect_throw("map context without DOM context")
+            .message_thunk()
+    }
+}
+
+impl<B: HeadlessBackend> MapCtx<B> {
+    /// Create a context to build the views without a browser
+    /// (see [`MapBackend`] for the views that support it).
+    #[must_use]
+    pub const fn with_backend(backend: B) -> Self {
+        Self {
+            backend,
+            dom_ctx: None,
+            id_path: Vec::new(),
+        }
     }
 }
 
-impl ViewPathTracker for MapCtx {
+impl<B: MapBackend> MapCtx<B> {
+    #[must_use]
+    pub const fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+}
+
+impl<B: MapBackend> ViewPathTracker for MapCtx<B> {
     fn push_id(&mut self, id: ViewId) {
-        self.dom_ctx.push_id(id);
+        match &mut self.dom_ctx {
+            Some(dom_ctx) => dom_ctx.push_id(id),
+            None => self.id_path.push(id),
+        }
     }
 
     fn pop_id(&mut self) {
-        self.dom_ctx.pop_id();
+        match &mut self.dom_ctx {
+            Some(dom_ctx) => dom_ctx.pop_id(),
+            None => {
+                self.id_path.pop();
+            }
+        }
     }
 
     fn view_path(&mut self) -> &[ViewId] {
-        self.dom_ctx.view_path()
+        match &mut self.dom_ctx {
+            Some(dom_ctx) => dom_ctx.view_path(),
+            None => &self.id_path,
+        }
     }
 }
 
@@ -53,6 +128,14 @@ impl<V, State, Action> MapChildren<State, Action> for V where
 {
 }
 
+/// A type-erased map child, e.g. to return different views from a function.
+///
+/// It is used boxed (i.e. `Box<AnyMapView<State>>`).
+/// When the type of the boxed view changes,
+/// the previous view is torn down and the new one is built in its place.
+pub type AnyMapView<State, Action = (), B = LeafletBackend> =
+    dyn AnyView<State, Action, MapCtx<B>, MapChildElement<B>, DynMessage>;
+
 pub fn map<State, Action, Children>(
     children: Children,
 ) -> Map<impl HtmlElement<State, Action>, State, Action, Children>
@@ -61,107 +144,134 @@ where
     Action: 'static,
     Children: MapChildren<State, Action>,
 {
-    let map_view =
-        frozen(|| html::div(()).style([style("width", "100%"), style("height", "100%")]));
+    let map_view = frozen(map_container);
     Map {
         map_view,
         zoom: None,
         center: None,
+        crs: Crs::default(),
+        resize: ResizeOptions::default(),
+        on_error: None,
         children,
         phantom: PhantomData,
     }
 }
 
+/// The default container of the [`map`] that fills its parent element.
+///
+/// Use it to add classes or attributes to the container (see [`Map::container`]).
+pub fn map_container<State, Action>() -> impl HtmlDivElement<State, Action>
+where
+    State: 'static,
+    Action: 'static,
+{
+    html::div(()).style([style("width", "100%"), style("height", "100%")])
+}
+
 #[derive(Debug)]
-pub enum MapChildElement {
-    Marker(leaflet::Marker),
-    TileLayer(leaflet::TileLayer),
+pub enum MapChildElement<B: MapBackend = LeafletBackend> {
+    Marker(B::Marker),
+    TileLayer(B::TileLayer),
+    GridLayer(leaflet::GridLayer),
+    /// A polyline or polygon
+    Polyline(leaflet::Polyline),
+    /// A layer of another type, e.g. of a [`custom_layer`](crate::custom_layer).
+    Layer(leaflet::Layer),
     Event,
+    /// A view that manages the elements of its children on its own (e.g. [`keyed`](crate::keyed)).
+    Group,
 }
 
-impl MapChildElement {
-    /// # Panics
-    ///
-    /// If it's not a marker.
-    pub fn as_marker_mut(&mut self) -> &mut leaflet::Marker {
+impl<B: MapBackend> MapChildElement<B> {
+    /// `None` if it's not a marker.
+    pub fn as_marker_mut(&mut self) -> Option<&mut B::Marker> {
         match self {
-            MapChildElement::Marker(marker) => marker,
-            _ => panic!("Element is not a marker"),
+            MapChildElement::Marker(marker) => Some(marker),
+            _ => None,
         }
     }
-    /// # Panics
-    ///
-    /// If it's not a tile layer.
-    pub fn as_tile_layer_mut(&mut self) -> &mut leaflet::TileLayer {
+    /// `None` if it's not a tile layer.
+    pub fn as_tile_layer_mut(&mut self) -> Option<&mut B::TileLayer> {
         match self {
-            MapChildElement::TileLayer(layer) => layer,
-            _ => panic!("Element is not a marker"),
+            MapChildElement::TileLayer(layer) => Some(layer),
+            _ => None,
+        }
+    }
+    /// `None` if it's not a grid layer.
+    pub fn as_grid_layer_mut(&mut self) -> Option<&mut leaflet::GridLayer> {
+        match self {
+            MapChildElement::GridLayer(layer) => Some(layer),
+            _ => None,
+        }
+    }
+    /// `None` if it's not a polyline (or polygon).
+    pub fn as_polyline_mut(&mut self) -> Option<&mut leaflet::Polyline> {
+        match self {
+            MapChildElement::Polyline(polyline) => Some(polyline),
+            _ => None,
+        }
+    }
+    /// `None` if it's not a layer of another type.
+    pub fn as_layer_mut(&mut self) -> Option<&mut leaflet::Layer> {
+        match self {
+            MapChildElement::Layer(layer) => Some(layer),
+            _ => None,
         }
     }
 }
 
-impl ViewElement for MapChildElement {
-    type Mut<'a> = &'a mut MapChildElement;
+impl<B: MapBackend> ViewElement for MapChildElement<B> {
+    type Mut<'a> = &'a mut MapChildElement<B>;
 }
 
-// Necessary for the `ViewSequence`
-// This could theoretically cast more specific elements into a (type-erased) `AnyMapChildElement` if necessary.
-impl SuperElement<MapChildElement, MapCtx> for MapChildElement {
-    fn upcast(_: &mut MapCtx, child: MapChildElement) -> Self {
+// Necessary for the `ViewSequence`.
+// All children share the same element type, so there is nothing to cast.
+impl<B: MapBackend> SuperElement<MapChildElement<B>, MapCtx<B>> for MapChildElement<B> {
+    fn upcast(_: &mut MapCtx<B>, child: MapChildElement<B>) -> Self {
         child
     }
 
     fn with_downcast_val<R>(
         this: Mut<'_, Self>,
-        f: impl FnOnce(Mut<'_, MapChildElement>) -> R,
+        f: impl FnOnce(Mut<'_, MapChildElement<B>>) -> R,
     ) -> (Self::Mut<'_>, R) {
         let r = f(this);
         (this, r)
     }
 }
 
-struct MapChildrenSplice<'a> {
-    idx: usize,
-    children: &'a mut Vec<MapChildElement>,
-}
-
-impl<'a> MapChildrenSplice<'a> {
-    pub(crate) fn new(children: &'a mut Vec<MapChildElement>) -> Self {
-        Self { idx: 0, children }
+// Necessary for the `AnyMapView`.
+impl<B: MapBackend> AnyElement<MapChildElement<B>, MapCtx<B>> for MapChildElement<B> {
+    fn replace_inner(this: Self::Mut<'_>, child: MapChildElement<B>) -> Self::Mut<'_> {
+        *this = child;
+        this
     }
 }
 
-// Hmm I wonder if we could provide some generic types for this, to make this simpler,
-// we could e.g. export `VecSplice` from xilem_web (again),
-// and implement that trait for `VecSplice`, when it's just necessary to maintain a Vec of elements...
-// The current naive implementation is rather inefficient O(n^2) (resulting in a lot of shifting of elements and allocations)
-impl ElementSplice<MapChildElement> for MapChildrenSplice<'_> {
-    fn with_scratch<R>(&mut self, f: impl FnOnce(&mut AppendVec<MapChildElement>) -> R) -> R {
+// The splice visits the children sequentially,
+// so a rebuild is linear in the number of children.
+impl<B: MapBackend> ElementSplice<MapChildElement<B>> for VecSplice<'_, '_, MapChildElement<B>> {
+    fn with_scratch<R>(&mut self, f: impl FnOnce(&mut AppendVec<MapChildElement<B>>) -> R) -> R {
         let mut scratch = AppendVec::default();
         let ret_val = f(&mut scratch);
-        let new_elements = scratch.into_inner();
-        let len = new_elements.len();
-        self.children.splice(self.idx..self.idx, new_elements);
-        self.idx += len;
+        self.extend(scratch.into_inner());
         ret_val
     }
 
-    fn insert(&mut self, element: MapChildElement) {
-        self.children.insert(self.idx, element);
-        self.idx += 1;
+    fn insert(&mut self, element: MapChildElement<B>) {
+        VecSplice::insert(self, element);
     }
 
-    fn mutate<R>(&mut self, f: impl FnOnce(Mut<'_, MapChildElement>) -> R) -> R {
-        self.idx += 1;
-        f(&mut self.children[self.idx - 1])
+    fn mutate<R>(&mut self, f: impl FnOnce(Mut<'_, MapChildElement<B>>) -> R) -> R {
+        f(VecSplice::mutate(self))
     }
 
     fn skip(&mut self, n: usize) {
-        self.idx += n;
+        VecSplice::skip(self, n);
     }
 
-    fn delete<R>(&mut self, f: impl FnOnce(Mut<'_, MapChildElement>) -> R) -> R {
-        f(&mut self.children.remove(self.idx))
+    fn delete<R>(&mut self, f: impl FnOnce(Mut<'_, MapChildElement<B>>) -> R) -> R {
+        f(&mut VecSplice::delete(self))
     }
 }
 
@@ -181,7 +291,10 @@ pub struct Map<MapDomView, State, Action, Children> {
     map_view: MapDomView,
     children: Children,
     zoom: Option<f64>,
-    center: Option<(f64, f64)>,
+    center: Option<LatLng>,
+    crs: Crs,
+    resize: ResizeOptions,
+    on_error: Option<Box<dyn Fn(&mut State, MapError)>>,
     phantom: PhantomData<fn() -> (State, Action)>,
 }
 
@@ -203,6 +316,9 @@ impl<MapDomView, State, Action, Children> Map<MapDomView, State, Action, Childre
             children,
             zoom,
             center,
+            crs,
+            resize,
+            on_error,
             phantom,
         } = self;
         let children = (children, on_zoom_end(callback));
@@ -211,6 +327,9 @@ impl<MapDomView, State, Action, Children> Map<MapDomView, State, Action, Childre
             children,
             zoom,
             center,
+            crs,
+            resize,
+            on_error,
             phantom,
         }
     }
@@ -227,6 +346,9 @@ impl<MapDomView, State, Action, Children> Map<MapDomView, State, Action, Childre
             children,
             zoom,
             center,
+            crs,
+            resize,
+            on_error,
             phantom,
         } = self;
         let children = (children, on_move_end(callback));
@@ -235,6 +357,9 @@ impl<MapDomView, State, Action, Children> Map<MapDomView, State, Action, Childre
             children,
             zoom,
             center,
+            crs,
+            resize,
+            on_error,
             phantom,
         }
     }
@@ -251,6 +376,9 @@ impl<MapDomView, State, Action, Children> Map<MapDomView, State, Action, Childre
             children,
             zoom,
             center,
+            crs,
+            resize,
+            on_error,
             phantom,
         } = self;
         let children = (children, on_mouse_click(callback));
@@ -259,12 +387,139 @@ impl<MapDomView, State, Action, Children> Map<MapDomView, State, Action, Childre
             children,
             zoom,
             center,
+            crs,
+            resize,
+            on_error,
+            phantom,
+        }
+    }
+
+    /// Receive the new size of the map (in pixels)
+    /// whenever the size of its container has changed.
+    pub fn on_resize<F>(
+        self,
+        callback: F,
+    ) -> Map<MapDomView, State, Action, (Children, OnResize<F>)>
+    where
+        F: Fn(&mut State, Point) + 'static,
+    {
+        let Self {
+            map_view,
+            children,
+            zoom,
+            center,
+            crs,
+            resize,
+            on_error,
+            phantom,
+        } = self;
+        let children = (children, on_resize(callback));
+        Map {
+            map_view,
+            children,
+            zoom,
+            center,
+            crs,
+            resize,
+            on_error,
             phantom,
         }
     }
 
-    pub fn center(mut self, lat: f64, lng: f64) -> Self {
-        self.center = Some((lat, lng));
+    /// Receive a [`MapHandle`] once the view of the map has been set,
+    /// e.g. to store it in the state for imperative calls.
+    pub fn on_ready<F>(self, callback: F) -> Map<MapDomView, State, Action, (Children, OnReady<F>)>
+    where
+        F: Fn(&mut State, MapHandle) + 'static,
+    {
+        let Self {
+            map_view,
+            children,
+            zoom,
+            center,
+            crs,
+            resize,
+            on_error,
+            phantom,
+        } = self;
+        let children = (children, on_ready(callback));
+        Map {
+            map_view,
+            children,
+            zoom,
+            center,
+            crs,
+            resize,
+            on_error,
+            phantom,
+        }
+    }
+
+    /// Set the initial center (and move the map when it changes).
+    ///
+    /// With a geographic CRS, a center that is not [valid](LatLng::validate)
+    /// is ignored and a [`MapError::InvalidCenter`] is reported (see [`Map::on_error`]).
+    pub fn center(mut self, center: impl Into<LatLng>) -> Self {
+        self.center = Some(center.into());
+        self
+    }
+
+    /// Set the coordinate reference system (default: [`Crs::EPSG3857`]).
+    ///
+    /// Note that the canvas based views (e.g. [`heatmap`](crate::heatmap))
+    /// and [`marker_cluster`](crate::marker_cluster) assume [`Crs::EPSG3857`].
+    ///
+    /// If the CRS can't be created (e.g. without Proj4Leaflet),
+    /// a [`MapError::Crs`] is reported (see [`Map::on_error`]).
+    pub fn crs(mut self, crs: Crs) -> Self {
+        self.crs = crs;
+        self
+    }
+
+    /// Use a custom container element instead of the [`map_container`]
+    /// (e.g. with classes, attributes or an explicit size).
+    ///
+    /// The container must not have children, its content is managed by Leaflet.
+    pub fn container<C>(self, container: C) -> Map<C, State, Action, Children>
+    where
+        C: HtmlElement<State, Action>,
+    {
+        let Self {
+            map_view: _,
+            children,
+            zoom,
+            center,
+            crs,
+            resize,
+            on_error,
+            phantom,
+        } = self;
+        Map {
+            map_view: container,
+            children,
+            zoom,
+            center,
+            crs,
+            resize,
+            on_error,
+            phantom,
+        }
+    }
+
+    /// Configure how the map is updated when the size of its container changes
+    /// (e.g. when a sidebar is collapsed).
+    pub fn resize_options(mut self, options: ResizeOptions) -> Self {
+        self.resize = options;
+        self
+    }
+
+    /// Handle the errors that prevent the map from being displayed as described
+    /// (they are logged if there is no callback).
+    pub fn on_error<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&mut State, MapError) + 'static,
+    {
+        self.on_error = Some(Box::new(callback));
         self
     }
 }
@@ -273,58 +528,120 @@ impl<Styles, State, Action, Children> ViewMarker for Map<Styles, State, Action,
 
 pub struct MapViewState<DS, CS> {
     map_dom_state: DS,
+    container: web_sys::HtmlElement,
+    /// `None` until the Leaflet assets have been loaded.
+    leaflet: Option<LeafletState<CS>>,
+}
+
+struct LeafletState<CS> {
     children_state: CS,
     children: Vec<MapChildElement>,
+    children_scratch: Vec<MapChildElement>,
     leaflet_map: leaflet::Map,
+    /// The classes that have been added to the container by Leaflet.
+    leaflet_classes: Vec<String>,
+    mount: Mount,
+    _live: LiveToken,
 }
 
 #[derive(Debug, Clone)]
 pub enum MapMessage {
+    /// The Leaflet assets have been loaded.
     InitMap,
+    Error(MapError),
 }
 
-impl<MapDomView, State, Action, Children> View<State, Action, ViewCtx, DynMessage>
-    for Map<MapDomView, State, Action, Children>
+impl<MapDomView, State, Action, Children> Map<MapDomView, State, Action, Children>
 where
     State: 'static,
     Action: 'static,
-    MapDomView: HtmlElement<State, Action>,
     Children: MapChildren<State, Action>,
 {
-    type Element = MapDomView::Element;
-
-    type ViewState = MapViewState<MapDomView::ViewState, Children::SeqState>;
-
-    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
-        let (map_dom_element, map_dom_state) = self.map_view.build(ctx);
+    /// The center, unless it is invalid for a geographic CRS (which is reported).
+    fn checked_center(&self, ctx: &mut ViewCtx) -> Option<LatLng> {
+        let center = self.center?;
+        if self.crs.is_geographic() {
+            if let Err(error) = center.validate() {
+                ctx.message_thunk()
+                    .enqueue_message(MapMessage::Error(MapError::InvalidCenter(error)));
+                return None;
+            }
+        }
+        Some(center)
+    }
 
+    fn init_leaflet(
+        &self,
+        container: &web_sys::HtmlElement,
+        ctx: &mut ViewCtx,
+    ) -> LeafletState<Children::SeqState> {
         let map_options = leaflet::MapOptions::default();
-        let leaflet_map =
-            leaflet::Map::new_with_element(map_dom_element.node.as_ref(), &map_options);
+        if let Err(error) = self.crs.apply_to_options(&map_options) {
+            ctx.message_thunk()
+                .enqueue_message(MapMessage::Error(error.into()));
+        }
+        let container_classes = container.class_name();
+        let leaflet_map = leaflet::Map::new_with_element(container, &map_options);
+        let leaflet_classes = container
+            .class_name()
+            .split_whitespace()
+            .filter(|class| !container_classes.split_whitespace().any(|c| c == *class))
+            .map(ToString::to_string)
+            .collect();
+        // The DOM element might not have been attached to the document yet.
+        let center = self.checked_center(ctx);
+        let mount = Mount::new(&leaflet_map, container, self.zoom, center, self.resize);
 
         let mut elements = AppendVec::default();
-        let view_state = ctx.as_owned(|dom_ctx| {
-            let mut map_ctx = MapCtx::new(dom_ctx, leaflet_map.clone());
+        ctx.as_owned(|dom_ctx| {
+            let mut map_ctx = MapCtx::new(dom_ctx, leaflet_map);
             let children_state = self.children.seq_build(&mut map_ctx, &mut elements);
-            let view_state = MapViewState {
-                leaflet_map: map_ctx.map,
-                map_dom_state,
+            let leaflet_state = LeafletState {
+                leaflet_map: map_ctx.map().clone(),
                 children: elements.into_inner(),
+                children_scratch: Vec::new(),
                 children_state,
+                leaflet_classes,
+                mount,
+                _live: LiveToken::map(),
             };
-            (map_ctx.dom_ctx, view_state)
-        });
+            (map_ctx.into_dom_ctx(), leaflet_state)
+        })
+    }
+}
 
-        // We have to postpone the map initiation
-        // because the DOM element has been created at this point in time
-        // but has not yet been mounted.
-        {
-            let map = view_state.leaflet_map.clone();
-            let zoom = self.zoom;
-            let center = self.center;
-            spawn_local(async move { apply_zoom_and_center(&map, zoom, center) });
-        }
+impl<MapDomView, State, Action, Children> View<State, Action, ViewCtx, DynMessage>
+    for Map<MapDomView, State, Action, Children>
+where
+    State: 'static,
+    Action: 'static,
+    MapDomView: HtmlElement<State, Action>,
+    Children: MapChildren<State, Action>,
+{
+    type Element = MapDomView::Element;
+
+    type ViewState = MapViewState<MapDomView::ViewState, Children::SeqState>;
 
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (map_dom_element, map_dom_state) = self.map_view.build(ctx);
+        let container: &web_sys::HtmlElement = map_dom_element.node.as_ref();
+        let leaflet = if leaflet_assets::is_loaded() {
+            Some(self.init_leaflet(container, ctx))
+        } else {
+            let thunk = ctx.message_thunk();
+            spawn_local(async move {
+                match leaflet_assets::load().await {
+                    Ok(()) => thunk.enqueue_message(MapMessage::InitMap),
+                    Err(error) => thunk.enqueue_message(MapMessage::Error(error.into())),
+                }
+            });
+            None
+        };
+        let view_state = MapViewState {
+            map_dom_state,
+            container: container.clone(),
+            leaflet,
+        };
         (map_dom_element, view_state)
     }
 
@@ -337,31 +654,60 @@ where
     ) {
         self.map_view
             .rebuild(&prev.map_view, &mut view_state.map_dom_state, ctx, element);
+        let Some(leaflet) = &mut view_state.leaflet else {
+            // Either after the `MapMessage::InitMap` or the page has loaded the assets meanwhile.
+            if leaflet_assets::is_loaded() {
+                view_state.leaflet = Some(self.init_leaflet(&view_state.container, ctx));
+            }
+            return;
+        };
+        restore_classes(&view_state.container, &leaflet.leaflet_classes);
+        if prev.crs != self.crs {
+            if let Err(error) = self.crs.apply_to_map(&leaflet.leaflet_map) {
+                ctx.message_thunk()
+                    .enqueue_message(MapMessage::Error(error.into()));
+            }
+        }
+        if prev.resize != self.resize {
+            leaflet.mount.set_resize_options(self.resize);
+        }
         if prev.zoom != self.zoom || prev.center != self.center {
-            apply_zoom_and_center(&view_state.leaflet_map, self.zoom, self.center);
+            let center = self.checked_center(ctx);
+            leaflet.mount.set_view(self.zoom, center);
         }
         ctx.as_owned(|dom_ctx| {
-            let mut map_ctx = MapCtx::new(dom_ctx, view_state.leaflet_map.clone());
+            let mut map_ctx = MapCtx::new(dom_ctx, leaflet.leaflet_map.clone());
             self.children.seq_rebuild(
                 &prev.children,
-                &mut view_state.children_state,
+                &mut leaflet.children_state,
                 &mut map_ctx,
-                &mut MapChildrenSplice::new(&mut view_state.children),
+                &mut VecSplice::new(&mut leaflet.children, &mut leaflet.children_scratch),
             );
-            (map_ctx.dom_ctx, ())
+            (map_ctx.into_dom_ctx(), ())
         });
     }
 
-    fn teardown(&self, view_state: &mut Self::ViewState, ctx: &mut ViewCtx, _: Mut<Self::Element>) {
-        ctx.as_owned(|dom_ctx| {
-            let mut map_ctx = MapCtx::new(dom_ctx, view_state.leaflet_map.clone());
-            self.children.seq_teardown(
-                &mut view_state.children_state,
-                &mut map_ctx,
-                &mut MapChildrenSplice::new(&mut view_state.children),
-            );
-            (map_ctx.dom_ctx, ())
-        });
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        element: Mut<Self::Element>,
+    ) {
+        if let Some(leaflet) = &mut view_state.leaflet {
+            ctx.as_owned(|dom_ctx| {
+                let mut map_ctx = MapCtx::new(dom_ctx, leaflet.leaflet_map.clone());
+                self.children.seq_teardown(
+                    &mut leaflet.children_state,
+                    &mut map_ctx,
+                    &mut VecSplice::new(&mut leaflet.children, &mut leaflet.children_scratch),
+                );
+                (map_ctx.into_dom_ctx(), ())
+            });
+            // Removes the DOM listeners and the layers that have been added by Leaflet.
+            leaflet.leaflet_map.remove();
+        }
+        self.map_view
+            .teardown(&mut view_state.map_dom_state, ctx, element);
     }
 
     fn message(
@@ -371,21 +717,94 @@ where
         message: DynMessage,
         app_state: &mut State,
     ) -> MessageResult<Action, DynMessage> {
+        if id_path.is_empty() {
+            return match message.downcast::<MapMessage>().map(|message| *message) {
+                Ok(MapMessage::InitMap) if view_state.leaflet.is_none() => {
+                    MessageResult::RequestRebuild
+                }
+                Ok(MapMessage::InitMap) => MessageResult::Nop,
+                Ok(MapMessage::Error(error)) => {
+                    match &self.on_error {
+                        Some(on_error) => on_error(app_state, error),
+                        None => log::error!("Unable to display the map: {error}"),
+                    }
+                    MessageResult::Nop
+                }
+                Err(message) => MessageResult::Stale(message),
+            };
+        }
+        let Some(leaflet) = &mut view_state.leaflet else {
+            return MessageResult::Stale(message);
+        };
         self.children
-            .seq_message(&mut view_state.children_state, id_path, message, app_state)
+            .seq_message(&mut leaflet.children_state, id_path, message, app_state)
     }
 }
 
-fn apply_zoom_and_center(map: &leaflet::Map, zoom: Option<f64>, center: Option<(f64, f64)>) {
-    log::debug!("apply zoom ({zoom:?} and center ({center:?})");
-    match (zoom, center) {
-        (Some(zoom), None) => {
-            map.set_zoom(zoom);
-        }
-        (Some(zoom), Some((lat, lng))) => {
-            let center = leaflet::LatLng::new(lat, lng);
-            map.set_view(&center, zoom);
-        }
-        _ => {}
+/// Add the classes that might have been removed by a rebuild of the container.
+fn restore_classes(container: &web_sys::Element, classes: &[String]) {
+    let class_name = container.class_name();
+    let missing: Vec<_> = classes
+        .iter()
+        .filter(|class| !class_name.split_whitespace().any(|c| c == class.as_str()))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        container.set_class_name(&format!("{class_name} {}", missing.join(" ")));
+    }
+}
+
+/// Check whether the view of the map has been set
+/// (Leaflet throws an error when accessing e.g. the bounds before).
+pub(crate) fn is_loaded(map: &leaflet::Map) -> bool {
+    web_sys::js_sys::Reflect::get(map, &"_loaded".into())
+        .ok()
+        .and_then(|loaded| loaded.as_bool())
+        .unwrap_or(false)
+}
+
+/// Prevent that a layer event is also fired on the map (like `L.DomEvent.stopPropagation`).
+pub(crate) fn stop_propagation(event: &JsValue) {
+    let stop_propagation = ["L", "DomEvent", "stopPropagation"].into_iter().try_fold(
+        JsValue::from(web_sys::js_sys::global()),
+        |object, key| {
+            Reflect::get(&object, &key.into())
+                .ok()
+                .filter(|value| !value.is_undefined())
+        },
+    );
+    if let Some(stop_propagation) = stop_propagation {
+        let stop_propagation: Function = stop_propagation.unchecked_into();
+        stop_propagation.call1(&JsValue::NULL, event).unwrap_throw();
     }
 }
+
+/// Check whether an interaction handler of the map (e.g. `dragging`) is enabled.
+pub(crate) fn handler_enabled(map: &leaflet::Map, handler: &str) -> bool {
+    Reflect::get(map, &handler.into())
+        .ok()
+        .filter(|handler| handler.is_object())
+        .and_then(|handler| {
+            let enabled: Function = Reflect::get(&handler, &"enabled".into())
+                .ok()?
+                .dyn_into()
+                .ok()?;
+            enabled.call0(&handler).ok()?.as_bool()
+        })
+        .unwrap_or(false)
+}
+
+/// Enable or disable an interaction handler of the map (e.g. `dragging`).
+pub(crate) fn set_handler_enabled(map: &leaflet::Map, handler: &str, enabled: bool) {
+    let Some(handler) = Reflect::get(map, &handler.into())
+        .ok()
+        .filter(|handler| handler.is_object())
+    else {
+        return;
+    };
+    let method = if enabled { "enable" } else { "disable" };
+    let method: Function = Reflect::get(&handler, &method.into())
+        .unwrap_throw()
+        .unchecked_into();
+    method.call0(&handler).unwrap_throw();
+}
@@ -0,0 +1,165 @@
+use std::{cell::RefCell, rc::Rc};
+
+use web_sys::{
+    js_sys::{Function, Object, Reflect},
+    wasm_bindgen::{closure::Closure, JsCast as _, JsValue, UnwrapThrowExt as _},
+};
+
+use super::TrackedClosure;
+use crate::LatLng;
+
+/// How the map is updated when the size of its container changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResizeOptions {
+    /// Keep the center of the map in place
+    /// (otherwise the top left corner stays in place).
+    pub preserve_center: bool,
+    /// Delay the `moveend` event while the container is resized continuously.
+    pub debounce_moveend: bool,
+}
+
+impl Default for ResizeOptions {
+    fn default() -> Self {
+        Self {
+            preserve_center: true,
+            debounce_moveend: false,
+        }
+    }
+}
+
+/// Sets the view of the map as soon as its container
+/// is attached to the document and has a size,
+/// and updates the map whenever the size of the container changes.
+///
+/// Leaflet calculates the bounds from the size of the container,
+/// so a view that is set before would be wrong.
+/// The layers that are added before are only rendered once the view has been set.
+pub(super) struct Mount {
+    state: Rc<RefCell<MountState>>,
+    observer: web_sys::ResizeObserver,
+    _on_resize: TrackedClosure<dyn Fn(JsValue)>,
+}
+
+struct MountState {
+    map: leaflet::Map,
+    mounted: bool,
+    zoom: Option<f64>,
+    center: Option<LatLng>,
+    resize_options: ResizeOptions,
+}
+
+impl Mount {
+    pub(super) fn new(
+        map: &leaflet::Map,
+        container: &web_sys::Element,
+        zoom: Option<f64>,
+        center: Option<LatLng>,
+        resize_options: ResizeOptions,
+    ) -> Self {
+        let state = Rc::new(RefCell::new(MountState {
+            map: map.clone(),
+            mounted: false,
+            zoom,
+            center,
+            resize_options,
+        }));
+        let on_resize = {
+            let state = Rc::clone(&state);
+            let container = container.clone();
+            Closure::<dyn Fn(JsValue)>::new(move |_| {
+                // A collapsed container (e.g. in a hidden tab) is ignored
+                // until it is expanded again.
+                if container.client_width() > 0 && container.client_height() > 0 {
+                    MountState::resize(&state);
+                }
+            })
+        };
+        let observer =
+            web_sys::ResizeObserver::new(on_resize.as_ref().unchecked_ref()).unwrap_throw();
+        observer.observe(container);
+        Self {
+            state,
+            observer,
+            _on_resize: TrackedClosure::new(on_resize),
+        }
+    }
+
+    /// Set the view now or as soon as the map has been mounted.
+    pub(super) fn set_view(&self, zoom: Option<f64>, center: Option<LatLng>) {
+        // The borrow must be released before Leaflet fires the events of the view change.
+        let map = {
+            let mut state = self.state.borrow_mut();
+            state.zoom = zoom;
+            state.center = center;
+            state.mounted.then(|| state.map.clone())
+        };
+        if let Some(map) = map {
+            apply_zoom_and_center(&map, zoom, center);
+        }
+    }
+
+    pub(super) fn set_resize_options(&self, resize_options: ResizeOptions) {
+        self.state.borrow_mut().resize_options = resize_options;
+    }
+}
+
+impl Drop for Mount {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
+
+impl MountState {
+    fn resize(this: &Rc<RefCell<Self>>) {
+        // The borrow must be released before Leaflet fires the events of the view change.
+        let (map, mounted, zoom, center, resize_options) = {
+            let mut state = this.borrow_mut();
+            let mounted = state.mounted;
+            state.mounted = true;
+            (
+                state.map.clone(),
+                mounted,
+                state.zoom,
+                state.center,
+                state.resize_options,
+            )
+        };
+        if mounted {
+            invalidate_size(&map, resize_options);
+        } else {
+            // The size has been cached while the container was detached.
+            map.invalidate_size(false);
+            apply_zoom_and_center(&map, zoom, center);
+        }
+    }
+}
+
+/// Update the map to the size of its container
+/// (Leaflet fires a `resize` event if it has changed).
+fn invalidate_size(map: &leaflet::Map, options: ResizeOptions) {
+    let js_options = Object::new();
+    Reflect::set(&js_options, &"pan".into(), &options.preserve_center.into()).unwrap_throw();
+    Reflect::set(
+        &js_options,
+        &"debounceMoveend".into(),
+        &options.debounce_moveend.into(),
+    )
+    .unwrap_throw();
+    let method: Function = Reflect::get(map, &"invalidateSize".into())
+        .unwrap_throw()
+        .unchecked_into();
+    method.call1(map, &js_options).unwrap_throw();
+}
+
+fn apply_zoom_and_center(map: &leaflet::Map, zoom: Option<f64>, center: Option<LatLng>) {
+    log::debug!("apply zoom ({zoom:?} and center ({center:?})");
+    match (zoom, center) {
+        (Some(zoom), None) => {
+            map.set_zoom(zoom);
+        }
+        (Some(zoom), Some(center)) => {
+            map.set_view(&center.into(), zoom);
+        }
+        _ => {}
+    }
+}
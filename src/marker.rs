@@ -3,41 +3,61 @@ use xilem_web::{
     DynMessage,
 };
 
-use crate::{MapChildElement, MapCtx};
+use crate::{LatLng, MapBackend, MapChildElement, MapCtx, Selectable};
 
-pub const fn marker(lat: f64, lng: f64) -> Marker {
-    Marker { lat, lng }
+/// A marker at the given position, e.g. `marker(LatLng::new(51.505, -0.09))`.
+pub fn marker(position: impl Into<LatLng>) -> Marker {
+    Marker {
+        position: position.into(),
+    }
 }
 
 #[derive(PartialEq)]
 pub struct Marker {
-    lat: f64,
-    lng: f64,
+    position: LatLng,
 }
 
 impl ViewMarker for Marker {}
 
-impl<State, Action> View<State, Action, MapCtx, DynMessage> for Marker {
-    type Element = MapChildElement;
+impl Selectable for Marker {
+    fn points(&self) -> &[LatLng] {
+        std::slice::from_ref(&self.position)
+    }
+}
+
+impl<State, Action, B: MapBackend> View<State, Action, MapCtx<B>, DynMessage> for Marker {
+    type Element = MapChildElement<B>;
 
     type ViewState = ();
 
-    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
-        let marker = leaflet::Marker::new(&leaflet::LatLng::new(self.lat, self.lng));
-        marker.add_to(ctx.map());
+    fn build(&self, ctx: &mut MapCtx<B>) -> (Self::Element, Self::ViewState) {
+        let marker = ctx.backend_mut().add_marker(self.position);
         (MapChildElement::Marker(marker), ())
     }
 
-    fn rebuild(&self, prev: &Self, _: &mut Self::ViewState, _: &mut MapCtx, e: Mut<Self::Element>) {
-        debug_assert!(matches!(e, MapChildElement::Marker(_)), "not a marker: {e:?}");
-        if self != prev {
-            e.as_marker_mut()
-                .set_lat_lng(&leaflet::LatLng::new(self.lat, self.lng));
+    fn rebuild(
+        &self,
+        prev: &Self,
+        _: &mut Self::ViewState,
+        ctx: &mut MapCtx<B>,
+        e: Mut<Self::Element>,
+    ) {
+        if self == prev {
+            return;
         }
+        let Some(marker) = e.as_marker_mut() else {
+            log::error!("The element of a marker is not a marker: {e:?}");
+            return;
+        };
+        ctx.backend_mut().set_marker_position(marker, self.position);
     }
 
-    fn teardown(&self, _: &mut Self::ViewState, _: &mut MapCtx, e: Mut<Self::Element>) {
-        e.as_marker_mut().remove();
+    fn teardown(&self, _: &mut Self::ViewState, ctx: &mut MapCtx<B>, e: Mut<Self::Element>) {
+        let Some(marker) = e.as_marker_mut() else {
+            log::error!("The element of a marker is not a marker: {e:?}");
+            return;
+        };
+        ctx.backend_mut().remove_marker(marker);
     }
 
     fn message(
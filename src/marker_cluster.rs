@@ -0,0 +1,189 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker},
+    DynMessage,
+};
+
+use crate::{
+    map::{is_loaded, EventListener},
+    Cluster, ClusterId, ClusterIndex, ClusterOptions, LatLng, LatLngBounds, MapChildElement,
+    MapCtx,
+};
+
+/// CSS class of the cluster markers.
+pub const MARKER_CLUSTER_CLASS_NAME: &str = "marker-cluster";
+
+/// Cluster icon size in pixels.
+const CLUSTER_ICON_SIZE: f64 = 40.0;
+
+/// Display a large number of points as clustered markers.
+///
+/// The clusters are calculated per zoom level
+/// and only the clusters within the current bounds are rendered.
+/// A cluster marker shows the number of its points
+/// (use the [`MARKER_CLUSTER_CLASS_NAME`] CSS class for styling)
+/// and a click on it zooms in until the cluster expands.
+///
+/// The clusters are only recalculated when `points` is not the same `Rc`
+/// as in the previous view or the options have changed.
+pub const fn marker_cluster(points: Rc<[LatLng]>, options: ClusterOptions) -> MarkerCluster {
+    MarkerCluster { points, options }
+}
+
+pub struct MarkerCluster {
+    points: Rc<[LatLng]>,
+    options: ClusterOptions,
+}
+
+impl ViewMarker for MarkerCluster {}
+
+pub struct MarkerClusterViewState {
+    layer: Rc<RefCell<ClusterLayer>>,
+    on_move_end: Option<EventListener<leaflet::Map>>,
+}
+
+struct ClusterLayer {
+    map: leaflet::Map,
+    index: ClusterIndex,
+    markers: HashMap<ClusterId, ClusterMarker>,
+    /// The map has a view (i.e. zoom and center) that the clusters can be rendered for.
+    ready: bool,
+}
+
+struct ClusterMarker {
+    marker: leaflet::Marker,
+    _on_click: Option<EventListener<leaflet::Marker>>,
+}
+
+impl ClusterLayer {
+    fn render(&mut self, this: &Weak<RefCell<Self>>) {
+        if !self.ready {
+            return;
+        }
+        let bounds = LatLngBounds::from(self.map.get_bounds());
+        let clusters = self.index.clusters(&bounds, self.map.get_zoom());
+        let mut markers = HashMap::with_capacity(clusters.len());
+        for cluster in clusters {
+            let marker = self
+                .markers
+                .remove(&cluster.id)
+                .unwrap_or_else(|| self.create_marker(&cluster, this));
+            markers.insert(cluster.id, marker);
+        }
+        self.clear();
+        self.markers = markers;
+    }
+
+    fn create_marker(&self, cluster: &Cluster, this: &Weak<RefCell<Self>>) -> ClusterMarker {
+        let lat_lng = leaflet::LatLng::from(cluster.position);
+        if cluster.count == 1 {
+            let marker = leaflet::Marker::new(&lat_lng);
+            marker.add_to(&self.map);
+            return ClusterMarker {
+                marker,
+                _on_click: None,
+            };
+        }
+        let icon_options = leaflet::DivIconOptions::new();
+        icon_options.set_html(cluster.count.to_string());
+        icon_options.set_class_name(MARKER_CLUSTER_CLASS_NAME.to_string());
+        icon_options.set_icon_size(leaflet::Point::new(CLUSTER_ICON_SIZE, CLUSTER_ICON_SIZE));
+        let marker_options = leaflet::MarkerOptions::new();
+        marker_options.set_icon(leaflet::DivIcon::new(&icon_options).into());
+        let marker = leaflet::Marker::new_with_options(&lat_lng, &marker_options);
+        let on_click = {
+            let id = cluster.id;
+            let layer = Weak::clone(this);
+            EventListener::new(&marker, "click", move |_: leaflet::Event| {
+                let Some(layer) = layer.upgrade() else {
+                    return;
+                };
+                // The borrow must be released before the view changes.
+                let (map, zoom) = {
+                    let layer = layer.borrow();
+                    (layer.map.clone(), layer.index.expansion_zoom(id))
+                };
+                map.set_view(&lat_lng, f64::from(zoom));
+            })
+        };
+        marker.add_to(&self.map);
+        ClusterMarker {
+            marker,
+            _on_click: Some(on_click),
+        }
+    }
+
+    fn clear(&mut self) {
+        for (_, cluster_marker) in self.markers.drain() {
+            cluster_marker.marker.remove();
+        }
+    }
+}
+
+impl<State, Action> View<State, Action, MapCtx, DynMessage> for MarkerCluster {
+    type Element = MapChildElement;
+
+    type ViewState = MarkerClusterViewState;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        let layer = Rc::new(RefCell::new(ClusterLayer {
+            map: ctx.map().clone(),
+            index: ClusterIndex::new(&self.points, self.options.clone()),
+            markers: HashMap::new(),
+            ready: is_loaded(ctx.map()),
+        }));
+        let weak_layer = Rc::downgrade(&layer);
+        layer.borrow_mut().render(&weak_layer);
+        let on_move_end = EventListener::new(ctx.map(), "moveend", move |_: leaflet::Event| {
+            let Some(layer) = weak_layer.upgrade() else {
+                return;
+            };
+            let mut layer_mut = layer.borrow_mut();
+            layer_mut.ready = true;
+            layer_mut.render(&weak_layer);
+        });
+        let view_state = MarkerClusterViewState {
+            layer,
+            on_move_end: Some(on_move_end),
+        };
+        (MapChildElement::Group, view_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        _: &mut MapCtx,
+        _: Mut<Self::Element>,
+    ) {
+        if Rc::ptr_eq(&prev.points, &self.points) && prev.options == self.options {
+            return;
+        }
+        let mut layer = view_state.layer.borrow_mut();
+        layer.index = ClusterIndex::new(&self.points, self.options.clone());
+        layer.clear();
+        layer.render(&Rc::downgrade(&view_state.layer));
+    }
+
+    fn teardown(&self, view_state: &mut Self::ViewState, _: &mut MapCtx, _: Mut<Self::Element>) {
+        view_state.on_move_end = None;
+        let mut layer = view_state.layer.borrow_mut();
+        layer.ready = false;
+        layer.clear();
+    }
+
+    fn message(
+        &self,
+        _: &mut Self::ViewState,
+        _: &[ViewId],
+        message: DynMessage,
+        _: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        MessageResult::Stale(message)
+    }
+}
@@ -0,0 +1,243 @@
+//! Geodesic measurements.
+//!
+//! Distances are in meters, areas in square meters and bearings
+//! in degrees clockwise from north.
+//! The spherical calculations use the same earth radius as Leaflet.
+//!
+//! This module does not depend on Leaflet and can be used natively.
+
+use std::f64::consts::PI;
+
+use crate::LatLng;
+
+/// Mean earth radius in meters (as used by `L.CRS.Earth`).
+pub const MEAN_EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// Semi-major axis of the WGS 84 ellipsoid in meters.
+const WGS84_A: f64 = 6_378_137.0;
+
+/// Flattening of the WGS 84 ellipsoid.
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+/// Maximum number of iterations of the Vincenty formula.
+const VINCENTY_MAX_ITERATIONS: usize = 200;
+
+/// Great-circle distance on a sphere (haversine formula).
+#[must_use]
+pub fn haversine_distance(from: LatLng, to: LatLng) -> f64 {
+    let lat1 = from.lat.to_radians();
+    let lat2 = to.lat.to_radians();
+    let sin_dlat = ((to.lat - from.lat).to_radians() / 2.0).sin();
+    let sin_dlng = ((to.lng - from.lng).to_radians() / 2.0).sin();
+    let a = sin_dlat * sin_dlat + lat1.cos() * lat2.cos() * sin_dlng * sin_dlng;
+    2.0 * MEAN_EARTH_RADIUS * a.sqrt().min(1.0).asin()
+}
+
+/// Distance on the WGS 84 ellipsoid (Vincenty's inverse formula).
+///
+/// Returns `None` if the formula does not converge,
+/// which can happen for nearly antipodal points.
+#[must_use]
+pub fn vincenty_distance(from: LatLng, to: LatLng) -> Option<f64> {
+    let b = WGS84_A * (1.0 - WGS84_F);
+    let l = (to.lng - from.lng).to_radians();
+    let u1 = ((1.0 - WGS84_F) * from.lat.to_radians().tan()).atan();
+    let u2 = ((1.0 - WGS84_F) * to.lat.to_radians().tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+
+    let mut lambda = l;
+    for _ in 0..VINCENTY_MAX_ITERATIONS {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+        .sqrt();
+        if sin_sigma == 0.0 {
+            // Coincident points
+            return Some(0.0);
+        }
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos_sq_alpha = 1.0 - sin_alpha * sin_alpha;
+        let cos_2sigma_m = if cos_sq_alpha == 0.0 {
+            // Equatorial line
+            0.0
+        } else {
+            cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha
+        };
+        let c = WGS84_F / 16.0 * cos_sq_alpha * (4.0 + WGS84_F * (4.0 - 3.0 * cos_sq_alpha));
+        let previous_lambda = lambda;
+        lambda = l
+            + (1.0 - c)
+                * WGS84_F
+                * sin_alpha
+                * (sigma
+                    + c * sin_sigma
+                        * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+        if (lambda - previous_lambda).abs() < 1e-12 {
+            let u_sq = cos_sq_alpha * (WGS84_A * WGS84_A - b * b) / (b * b);
+            let a =
+                1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let b_coef = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = b_coef
+                * sin_sigma
+                * (cos_2sigma_m
+                    + b_coef / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                            - b_coef / 6.0
+                                * cos_2sigma_m
+                                * (-3.0 + 4.0 * sin_sigma.powi(2))
+                                * (-3.0 + 4.0 * cos_2sigma_m.powi(2))));
+            return Some(b * a * (sigma - delta_sigma));
+        }
+    }
+    None
+}
+
+/// Initial bearing of the great circle from one point to another
+/// (in the range `[0, 360)`).
+#[must_use]
+pub fn bearing(from: LatLng, to: LatLng) -> f64 {
+    let lat1 = from.lat.to_radians();
+    let lat2 = to.lat.to_radians();
+    let dlng = (to.lng - from.lng).to_radians();
+    let y = dlng.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlng.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// The point at the given distance and initial bearing along a great circle.
+#[must_use]
+pub fn destination(start: LatLng, bearing: f64, distance: f64) -> LatLng {
+    let lat1 = start.lat.to_radians();
+    let lng1 = start.lng.to_radians();
+    let bearing = bearing.to_radians();
+    let angle = distance / MEAN_EARTH_RADIUS;
+    let lat2 = (lat1.sin() * angle.cos() + lat1.cos() * angle.sin() * bearing.cos()).asin();
+    let lng2 = lng1
+        + (bearing.sin() * angle.sin() * lat1.cos()).atan2(angle.cos() - lat1.sin() * lat2.sin());
+    // Normalize the longitude to [-180, 180)
+    let lng2 = (lng2 + PI).rem_euclid(2.0 * PI) - PI;
+    LatLng::new(lat2.to_degrees(), lng2.to_degrees())
+}
+
+/// Length of a line through all points (haversine formula).
+#[must_use]
+pub fn line_length(points: &[LatLng]) -> f64 {
+    points
+        .windows(2)
+        .map(|pair| haversine_distance(pair[0], pair[1]))
+        .sum()
+}
+
+/// Area of a polygon on a sphere.
+///
+/// The polygon is closed implicitly, i.e. the first point
+/// must not be repeated at the end.
+#[must_use]
+pub fn polygon_area(points: &[LatLng]) -> f64 {
+    if points.len() < 3 {
+        return 0.0;
+    }
+    let sum: f64 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(p1, p2)| {
+            (p2.lng - p1.lng).to_radians()
+                * (2.0 + p1.lat.to_radians().sin() + p2.lat.to_radians().sin())
+        })
+        .sum();
+    (sum * MEAN_EARTH_RADIUS * MEAN_EARTH_RADIUS / 2.0).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dms(degrees: f64, minutes: f64, seconds: f64) -> f64 {
+        degrees.signum() * (degrees.abs() + minutes / 60.0 + seconds / 3600.0)
+    }
+
+    #[test]
+    fn haversine() {
+        let distance = haversine_distance(LatLng::new(0.0, 0.0), LatLng::new(0.0, 1.0));
+        assert!((distance - MEAN_EARTH_RADIUS * PI / 180.0).abs() < 1e-6);
+        let a = LatLng::new(50.066_389, -5.714_722);
+        let b = LatLng::new(58.643_889, -3.07);
+        assert!((haversine_distance(a, b) - 968_853.5).abs() < 1.0);
+        assert!((line_length(&[a, b, a]) - 2.0 * haversine_distance(a, b)).abs() < 1e-6);
+        assert!(line_length(&[a]).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn vincenty() {
+        // The example of Vincenty's paper (Flinders Peak to Buninyong)
+        let flinders_peak = LatLng::new(dms(-37.0, 57.0, 3.720_30), dms(144.0, 25.0, 29.524_40));
+        let buninyong = LatLng::new(dms(-37.0, 39.0, 10.156_10), dms(143.0, 55.0, 35.383_90));
+        let distance = vincenty_distance(flinders_peak, buninyong).unwrap();
+        assert!((distance - 54_972.271).abs() < 1e-3);
+
+        // One degree along the equator
+        let distance = vincenty_distance(LatLng::new(0.0, 0.0), LatLng::new(0.0, 1.0)).unwrap();
+        assert!((distance - WGS84_A * PI / 180.0).abs() < 1e-6);
+
+        let point = LatLng::new(10.0, 20.0);
+        assert_eq!(vincenty_distance(point, point), Some(0.0));
+        // Nearly antipodal points
+        assert_eq!(
+            vincenty_distance(LatLng::new(0.0, 0.0), LatLng::new(0.5, 179.7)),
+            None
+        );
+    }
+
+    #[test]
+    fn bearings() {
+        let origin = LatLng::new(0.0, 0.0);
+        assert!(bearing(origin, LatLng::new(1.0, 0.0)).abs() < 1e-9);
+        assert!((bearing(origin, LatLng::new(0.0, 1.0)) - 90.0).abs() < 1e-9);
+        assert!((bearing(origin, LatLng::new(-1.0, 0.0)) - 180.0).abs() < 1e-9);
+        assert!((bearing(origin, LatLng::new(0.0, -1.0)) - 270.0).abs() < 1e-9);
+        // Baghdad to Osaka
+        let bearing = bearing(LatLng::new(35.0, 45.0), LatLng::new(35.0, 135.0));
+        assert!((bearing - dms(60.0, 9.0, 45.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn destinations() {
+        let start = LatLng::new(dms(53.0, 19.0, 14.0), -dms(1.0, 43.0, 47.0));
+        let end = destination(start, dms(96.0, 1.0, 18.0), 124_800.0);
+        assert!((end.lat - dms(53.0, 11.0, 18.0)).abs() < 1e-3);
+        assert!((end.lng - dms(0.0, 8.0, 0.0)).abs() < 1e-3);
+
+        let to = LatLng::new(-33.9, 151.2);
+        let end = destination(start, bearing(start, to), haversine_distance(start, to));
+        assert!((end.lat - to.lat).abs() < 1e-6);
+        assert!((end.lng - to.lng).abs() < 1e-6);
+
+        // Across the antimeridian
+        let end = destination(
+            LatLng::new(0.0, 179.5),
+            90.0,
+            MEAN_EARTH_RADIUS * PI / 180.0,
+        );
+        assert!((end.lng + 179.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn polygon_areas() {
+        let square = [
+            LatLng::new(0.0, 0.0),
+            LatLng::new(0.0, 1.0),
+            LatLng::new(1.0, 1.0),
+            LatLng::new(1.0, 0.0),
+        ];
+        // The area of the spherical rectangle
+        let expected = MEAN_EARTH_RADIUS.powi(2) * 1_f64.to_radians() * 1_f64.to_radians().sin();
+        assert!((polygon_area(&square) - expected).abs() < 1e-3);
+        let mut clockwise = square;
+        clockwise.reverse();
+        assert!((polygon_area(&clockwise) - expected).abs() < 1e-3);
+        assert!(polygon_area(&square[..2]).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,197 @@
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+
+use web_sys::{js_sys::Array, wasm_bindgen::UnwrapThrowExt as _};
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker as _},
+    DynMessage,
+};
+
+use crate::{
+    geometry::lat_lng_array,
+    map::EventListener,
+    measure::{haversine_distance, line_length, polygon_area},
+    LatLng, MapChildElement, MapCtx, Shape,
+};
+
+/// Distinctive ID for better debugging
+const MEASURE_TOOL_ID: ViewId = ViewId::new(23671);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MeasureMode {
+    /// Measure the length of a line.
+    #[default]
+    Distance,
+    /// Measure the area of a polygon.
+    Area,
+}
+
+/// The result of a measurement.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Measurement {
+    /// The points that have been clicked.
+    pub points: Vec<LatLng>,
+    /// Length of the line (the perimeter in [`MeasureMode::Area`]) in meters.
+    pub distance: f64,
+    /// Area in square meters ([`MeasureMode::Area`] only).
+    pub area: Option<f64>,
+}
+
+impl Measurement {
+    fn new(points: &[LatLng], mode: MeasureMode) -> Self {
+        let (distance, area) = match mode {
+            MeasureMode::Distance => (line_length(points), None),
+            MeasureMode::Area => {
+                let mut perimeter = line_length(points);
+                if let [first, _, .., last] = points {
+                    perimeter += haversine_distance(*last, *first);
+                }
+                (perimeter, Some(polygon_area(points)))
+            }
+        };
+        Self {
+            points: points.to_vec(),
+            distance,
+            area,
+        }
+    }
+}
+
+/// Measure distances or areas by clicking on the map.
+///
+/// Each click adds a point and the measurement is reported to `on_change`.
+/// A right click (`contextmenu`) starts a new measurement.
+pub fn measure_tool<State, F>(mode: MeasureMode, on_change: F) -> MeasureTool<State, F>
+where
+    F: Fn(&mut State, Measurement) + 'static,
+{
+    MeasureTool {
+        mode,
+        on_change,
+        phantom: PhantomData,
+    }
+}
+
+pub struct MeasureTool<State, F> {
+    mode: MeasureMode,
+    on_change: F,
+    phantom: PhantomData<fn() -> State>,
+}
+
+impl<State, F> ViewMarker for MeasureTool<State, F> {}
+
+#[derive(Debug)]
+struct MeasurementChanged;
+
+pub struct MeasureToolViewState {
+    layer: Rc<RefCell<MeasureLayer>>,
+    listeners: Vec<EventListener<leaflet::Map>>,
+}
+
+struct MeasureLayer {
+    polyline: leaflet::Polyline,
+    points: Vec<LatLng>,
+    mode: MeasureMode,
+}
+
+impl MeasureLayer {
+    fn draw(&self) {
+        let lat_lngs = if self.mode == MeasureMode::Area && self.points.len() > 2 {
+            Shape::Polygon(self.points.clone()).outline()
+        } else {
+            self.points.clone()
+        };
+        self.polyline.set_lat_lngs(&lat_lng_array(&lat_lngs));
+    }
+}
+
+impl<State, Action, F> View<State, Action, MapCtx, DynMessage> for MeasureTool<State, F>
+where
+    State: 'static,
+    F: Fn(&mut State, Measurement) + 'static,
+{
+    type Element = MapChildElement;
+
+    type ViewState = MeasureToolViewState;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        ctx.with_id(MEASURE_TOOL_ID, |ctx| {
+            let map = ctx.map();
+            let polyline = leaflet::Polyline::new(&Array::new());
+            polyline.add_to(map);
+            let layer = Rc::new(RefCell::new(MeasureLayer {
+                polyline,
+                points: Vec::new(),
+                mode: self.mode,
+            }));
+            let thunk = Rc::new(ctx.message_thunk());
+            let listeners = vec![
+                {
+                    let layer = Rc::clone(&layer);
+                    let thunk = Rc::clone(&thunk);
+                    EventListener::new(map, "click", move |ev: leaflet::MouseEvent| {
+                        {
+                            let mut layer = layer.borrow_mut();
+                            layer.points.push(ev.lat_lng().into());
+                            layer.draw();
+                        }
+                        // The borrow must be released before the message is handled.
+                        thunk.push_message(MeasurementChanged);
+                    })
+                },
+                {
+                    let layer = Rc::clone(&layer);
+                    EventListener::new(map, "contextmenu", move |_: leaflet::MouseEvent| {
+                        {
+                            let mut layer = layer.borrow_mut();
+                            layer.points.clear();
+                            layer.draw();
+                        }
+                        thunk.push_message(MeasurementChanged);
+                    })
+                },
+            ];
+            let view_state = MeasureToolViewState { layer, listeners };
+            (MapChildElement::Group, view_state)
+        })
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx,
+        _: Mut<Self::Element>,
+    ) {
+        ctx.with_id(MEASURE_TOOL_ID, |ctx| {
+            if prev.mode == self.mode {
+                return;
+            }
+            let mut layer = view_state.layer.borrow_mut();
+            layer.mode = self.mode;
+            layer.draw();
+            // The measurement of the same points differs between the modes.
+            ctx.message_thunk().enqueue_message(MeasurementChanged);
+        });
+    }
+
+    fn teardown(&self, view_state: &mut Self::ViewState, ctx: &mut MapCtx, _: Mut<Self::Element>) {
+        ctx.with_id(MEASURE_TOOL_ID, |_| {
+            view_state.listeners.clear();
+            view_state.layer.borrow().polyline.remove();
+        });
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        state: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        debug_assert!(id_path.len() == 1 && id_path[0] == MEASURE_TOOL_ID);
+        let MeasurementChanged = *message.downcast().unwrap_throw();
+        let measurement = Measurement::new(&view_state.layer.borrow().points, self.mode);
+        (self.on_change)(state, measurement);
+        MessageResult::Nop
+    }
+}
@@ -0,0 +1,359 @@
+use std::{borrow::Cow, cell::RefCell, collections::HashMap, marker::PhantomData, rc::Rc};
+
+use web_sys::wasm_bindgen::UnwrapThrowExt as _;
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker as _},
+    DynMessage,
+};
+
+use crate::{
+    canvas_overlay::{CanvasOverlay, FrameThrottle},
+    map::EventListener,
+    projection::{self, lat_y, lng_x},
+    LatLng, MapChildElement, MapCtx,
+};
+
+/// Additional distance in pixels within which a point is hit.
+const HIT_TOLERANCE: f64 = 3.0;
+
+/// Distinctive ID for better debugging
+const POINT_CLOUD_ID: ViewId = ViewId::new(23670);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointStyle {
+    /// Radius in pixels.
+    pub radius: f64,
+    /// CSS color.
+    pub color: Cow<'static, str>,
+}
+
+impl Default for PointStyle {
+    fn default() -> Self {
+        Self {
+            radius: 3.0,
+            color: Cow::Borrowed("#3388ff"),
+        }
+    }
+}
+
+/// Draw a large number of points onto a single canvas.
+///
+/// In contrast to [`marker`](crate::marker) no Leaflet layer
+/// is created per point.
+/// The points are only projected again when `points` is not the same `Rc`
+/// as in the previous view, and `style` is only called (with the index of each point)
+/// when the points or the `style_key` change.
+pub fn point_cloud<State, K, F>(
+    points: Rc<[LatLng]>,
+    style_key: K,
+    style: F,
+) -> PointCloud<State, K, F>
+where
+    K: PartialEq + 'static,
+    F: Fn(usize) -> PointStyle + 'static,
+{
+    PointCloud {
+        points,
+        style_key,
+        style,
+        on_click: None,
+        on_hover: None,
+        phantom: PhantomData,
+    }
+}
+
+type PointCallback<State, P> = Box<dyn Fn(&mut State, P)>;
+
+pub struct PointCloud<State, K, F> {
+    points: Rc<[LatLng]>,
+    style_key: K,
+    style: F,
+    on_click: Option<PointCallback<State, usize>>,
+    on_hover: Option<PointCallback<State, Option<usize>>>,
+    phantom: PhantomData<fn() -> State>,
+}
+
+impl<State, K, F> PointCloud<State, K, F> {
+    /// Receive the index of the nearest point that has been clicked.
+    #[must_use]
+    pub fn on_click<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&mut State, usize) + 'static,
+    {
+        self.on_click = Some(Box::new(callback));
+        self
+    }
+
+    /// Receive the index of the nearest point under the mouse pointer (if any).
+    #[must_use]
+    pub fn on_hover<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&mut State, Option<usize>) + 'static,
+    {
+        self.on_hover = Some(Box::new(callback));
+        self
+    }
+
+    fn styles(&self) -> Vec<PointStyle>
+    where
+        F: Fn(usize) -> PointStyle,
+    {
+        (0..self.points.len()).map(&self.style).collect()
+    }
+}
+
+impl<State, K, F> ViewMarker for PointCloud<State, K, F> {}
+
+#[derive(Debug)]
+enum PointCloudMessage {
+    Click(usize),
+    Hover(Option<usize>),
+}
+
+pub struct PointCloudViewState {
+    layer: Rc<RefCell<PointCloudLayer>>,
+    listeners: Vec<EventListener<leaflet::Map>>,
+    /// Redraws the points once per frame while the map is moved.
+    redraw: Option<Rc<FrameThrottle>>,
+}
+
+struct PointCloudLayer {
+    map: leaflet::Map,
+    overlay: CanvasOverlay,
+    /// Web mercator coordinates in the range `[0, 1]`.
+    projected: Vec<(f64, f64)>,
+    /// Point indices sorted by their x coordinate.
+    sorted_by_x: Vec<usize>,
+    /// Point indices grouped by their style to draw them in batches.
+    groups: Vec<(PointStyle, Vec<usize>)>,
+    /// The radius of each point in pixels.
+    radii: Vec<f64>,
+    max_radius: f64,
+    hovered: Option<usize>,
+}
+
+impl PointCloudLayer {
+    fn new(map: leaflet::Map, points: &[LatLng], styles: &[PointStyle]) -> Self {
+        let overlay = CanvasOverlay::new(&map);
+        let mut layer = Self {
+            map,
+            overlay,
+            projected: Vec::new(),
+            sorted_by_x: Vec::new(),
+            groups: Vec::new(),
+            radii: Vec::new(),
+            max_radius: 0.0,
+            hovered: None,
+        };
+        layer.set_points(points);
+        layer.set_styles(styles);
+        layer
+    }
+
+    fn set_points(&mut self, points: &[LatLng]) {
+        self.projected = points
+            .iter()
+            .map(|point| (lng_x(point.lng), lat_y(point.lat)))
+            .collect();
+        self.sorted_by_x = (0..points.len()).collect();
+        self.sorted_by_x
+            .sort_unstable_by(|a, b| self.projected[*a].0.total_cmp(&self.projected[*b].0));
+        self.hovered = None;
+    }
+
+    fn set_styles(&mut self, styles: &[PointStyle]) {
+        debug_assert_eq!(self.projected.len(), styles.len());
+        let mut group_indices = HashMap::new();
+        self.groups.clear();
+        for (idx, style) in styles.iter().enumerate() {
+            let group = *group_indices
+                .entry((style.radius.to_bits(), &style.color))
+                .or_insert_with(|| {
+                    self.groups.push((style.clone(), Vec::new()));
+                    self.groups.len() - 1
+                });
+            self.groups[group].1.push(idx);
+        }
+        self.radii = styles.iter().map(|style| style.radius).collect();
+        self.max_radius = self.radii.iter().copied().fold(0.0, f64::max);
+    }
+
+    fn scale(&self) -> f64 {
+        projection::scale(self.map.get_zoom())
+    }
+
+    fn draw(&self) {
+        let pixel_ratio = web_sys::window().unwrap_throw().device_pixel_ratio();
+        let Some((ctx, viewport)) = self.overlay.reset(pixel_ratio) else {
+            return;
+        };
+        for (style, indices) in &self.groups {
+            ctx.begin_path();
+            for &idx in indices {
+                let (x, y) = viewport.to_pixels(self.projected[idx]);
+                if x < -style.radius
+                    || y < -style.radius
+                    || x > viewport.width + style.radius
+                    || y > viewport.height + style.radius
+                {
+                    continue;
+                }
+                ctx.move_to(x + style.radius, y);
+                ctx.arc(x, y, style.radius, 0.0, std::f64::consts::TAU)
+                    .unwrap_throw();
+            }
+            ctx.set_fill_style_str(&style.color);
+            ctx.fill();
+        }
+    }
+
+    /// Find the nearest point within its radius (plus some tolerance).
+    fn hit_test(&self, lat_lng: &leaflet::LatLng) -> Option<usize> {
+        let scale = self.scale();
+        let x = lng_x(lat_lng.lng()) * scale;
+        let y = lat_y(lat_lng.lat()) * scale;
+        // No point can be hit from further away.
+        let max_distance = self.max_radius + HIT_TOLERANCE;
+        let min_x = (x - max_distance) / scale;
+        let start = self
+            .sorted_by_x
+            .partition_point(|idx| self.projected[*idx].0 < min_x);
+        let max_x = (x + max_distance) / scale;
+        self.sorted_by_x[start..]
+            .iter()
+            .take_while(|idx| self.projected[**idx].0 <= max_x)
+            .map(|&idx| {
+                let (px, py) = self.projected[idx];
+                let dx = px * scale - x;
+                let dy = py * scale - y;
+                (idx, (dx * dx + dy * dy).sqrt())
+            })
+            .filter(|&(idx, distance)| distance <= self.radii[idx] + HIT_TOLERANCE)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(idx, _)| idx)
+    }
+}
+
+impl<State, Action, K, F> View<State, Action, MapCtx, DynMessage> for PointCloud<State, K, F>
+where
+    State: 'static,
+    K: PartialEq + 'static,
+    F: Fn(usize) -> PointStyle + 'static,
+{
+    type Element = MapChildElement;
+
+    type ViewState = PointCloudViewState;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        ctx.with_id(POINT_CLOUD_ID, |ctx| {
+            let map = ctx.map();
+            let layer = Rc::new(RefCell::new(PointCloudLayer::new(
+                map.clone(),
+                &self.points,
+                &self.styles(),
+            )));
+            let redraw = Rc::new(FrameThrottle::new({
+                let layer = Rc::clone(&layer);
+                move || layer.borrow().draw()
+            }));
+            let mut listeners = ["move", "zoomend", "resize"]
+                .into_iter()
+                .map(|event_type| {
+                    let redraw = Rc::clone(&redraw);
+                    EventListener::new(map, event_type, move |_: leaflet::Event| {
+                        redraw.request();
+                    })
+                })
+                .collect::<Vec<_>>();
+            // The messages are handled synchronously like the clicks on the map
+            // (see `on_mouse_click`), so the borrows of the layer must be released before.
+            let thunk = Rc::new(ctx.message_thunk());
+            listeners.push({
+                let layer = Rc::clone(&layer);
+                let thunk = Rc::clone(&thunk);
+                EventListener::new(map, "click", move |ev: leaflet::MouseEvent| {
+                    // The borrow must be released before the message is handled.
+                    let hit = layer.borrow().hit_test(&ev.lat_lng());
+                    if let Some(idx) = hit {
+                        thunk.push_message(PointCloudMessage::Click(idx));
+                    }
+                })
+            });
+            listeners.push({
+                let layer = Rc::clone(&layer);
+                EventListener::new(map, "mousemove", move |ev: leaflet::MouseEvent| {
+                    let changed = {
+                        let mut layer = layer.borrow_mut();
+                        let hovered = layer.hit_test(&ev.lat_lng());
+                        let changed = hovered != layer.hovered;
+                        layer.hovered = hovered;
+                        changed.then_some(hovered)
+                    };
+                    if let Some(hovered) = changed {
+                        thunk.push_message(PointCloudMessage::Hover(hovered));
+                    }
+                })
+            });
+            layer.borrow().draw();
+            let view_state = PointCloudViewState {
+                layer,
+                listeners,
+                redraw: Some(redraw),
+            };
+            (MapChildElement::Group, view_state)
+        })
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx,
+        _: Mut<Self::Element>,
+    ) {
+        ctx.with_id(POINT_CLOUD_ID, |_| {
+            let points_changed = !Rc::ptr_eq(&prev.points, &self.points);
+            if !points_changed && prev.style_key == self.style_key {
+                return;
+            }
+            let mut layer = view_state.layer.borrow_mut();
+            if points_changed {
+                layer.set_points(&self.points);
+            }
+            layer.set_styles(&self.styles());
+            layer.draw();
+        });
+    }
+
+    fn teardown(&self, view_state: &mut Self::ViewState, ctx: &mut MapCtx, _: Mut<Self::Element>) {
+        ctx.with_id(POINT_CLOUD_ID, |_| {
+            view_state.listeners.clear();
+            // Cancels a pending redraw.
+            view_state.redraw = None;
+            view_state.layer.borrow().overlay.remove();
+        });
+    }
+
+    fn message(
+        &self,
+        _: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        state: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        debug_assert!(id_path.len() == 1 && id_path[0] == POINT_CLOUD_ID);
+        match *message.downcast().unwrap_throw() {
+            PointCloudMessage::Click(idx) => {
+                if let Some(on_click) = &self.on_click {
+                    on_click(state, idx);
+                }
+            }
+            PointCloudMessage::Hover(idx) => {
+                if let Some(on_hover) = &self.on_hover {
+                    on_hover(state, idx);
+                }
+            }
+        }
+        MessageResult::Nop
+    }
+}
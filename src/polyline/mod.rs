@@ -0,0 +1,328 @@
+mod vertices;
+
+use std::{
+    cell::RefCell,
+    marker::PhantomData,
+    rc::{Rc, Weak},
+};
+
+use web_sys::wasm_bindgen::{JsCast as _, UnwrapThrowExt as _};
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker as _},
+    DynMessage, MessageThunk,
+};
+
+use self::vertices::{edit_handles, edited_points, removed_vertex, HandleKind};
+use crate::{
+    draw_control::create_handle_marker, geometry::lat_lng_array, map::EventListener, LatLng,
+    MapChildElement, MapCtx, Selectable, DRAW_HANDLE_CLASS_NAME,
+};
+
+/// CSS class of the handles between two vertices that insert a new vertex when dragged
+/// (in addition to [`DRAW_HANDLE_CLASS_NAME`]).
+pub const GHOST_HANDLE_CLASS_NAME: &str = "draw-ghost-handle";
+
+/// Distinctive ID for better debugging
+const POLYLINE_ID: ViewId = ViewId::new(23673);
+
+pub const fn polyline<State>(points: Vec<LatLng>) -> Polyline<State> {
+    Polyline::new(points, false)
+}
+
+/// A polygon is a polyline that is closed implicitly.
+pub const fn polygon<State>(points: Vec<LatLng>) -> Polyline<State> {
+    Polyline::new(points, true)
+}
+
+type EditCallback<State> = Box<dyn Fn(&mut State, Vec<LatLng>)>;
+
+pub struct Polyline<State> {
+    points: Vec<LatLng>,
+    closed: bool,
+    editable: bool,
+    on_edit: Option<EditCallback<State>>,
+    phantom: PhantomData<fn() -> State>,
+}
+
+impl<State> Polyline<State> {
+    const fn new(points: Vec<LatLng>, closed: bool) -> Self {
+        Self {
+            points,
+            closed,
+            editable: false,
+            on_edit: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Show handles to edit the vertices.
+    ///
+    /// A vertex is moved by dragging its handle and removed by a right click (or long press).
+    /// Dragging a handle between two vertices inserts a new vertex.
+    /// The points are only changed when the view is rebuilt with the edited
+    /// points (see [`Polyline::on_edit`]).
+    #[must_use]
+    pub const fn editable(mut self, editable: bool) -> Self {
+        self.editable = editable;
+        self
+    }
+
+    /// Receive the edited points.
+    #[must_use]
+    pub fn on_edit<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&mut State, Vec<LatLng>) + 'static,
+    {
+        self.on_edit = Some(Box::new(callback));
+        self
+    }
+
+    fn create_layer(&self) -> leaflet::Polyline {
+        let lat_lngs = lat_lng_array(&self.points);
+        if self.closed {
+            leaflet::Polygon::new(&lat_lngs).unchecked_into()
+        } else {
+            leaflet::Polyline::new(&lat_lngs)
+        }
+    }
+}
+
+impl<State> ViewMarker for Polyline<State> {}
+
+impl<State> Selectable for Polyline<State> {
+    fn points(&self) -> &[LatLng] {
+        &self.points
+    }
+}
+
+#[derive(Debug)]
+struct PointsEdited(Vec<LatLng>);
+
+pub struct PolylineViewState {
+    editor: Option<Rc<RefCell<VertexEditor>>>,
+}
+
+struct VertexEditor {
+    map: leaflet::Map,
+    polyline: leaflet::Polyline,
+    thunk: Rc<MessageThunk>,
+    points: Vec<LatLng>,
+    closed: bool,
+    handles: Vec<EditHandle>,
+}
+
+struct EditHandle {
+    marker: leaflet::Marker,
+    _listeners: Vec<EventListener<leaflet::Marker>>,
+}
+
+impl VertexEditor {
+    fn new(
+        map: &leaflet::Map,
+        polyline: &leaflet::Polyline,
+        thunk: MessageThunk,
+        closed: bool,
+    ) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            map: map.clone(),
+            polyline: polyline.clone(),
+            thunk: Rc::new(thunk),
+            points: Vec::new(),
+            closed,
+            handles: Vec::new(),
+        }))
+    }
+
+    /// Show the handles of the points (the polyline must already show them).
+    fn set_points(&mut self, points: &[LatLng], this: &Weak<RefCell<Self>>) {
+        points.clone_into(&mut self.points);
+        self.remove_handles();
+        self.handles = edit_handles(points, self.closed)
+            .into_iter()
+            .map(|(kind, position)| self.create_handle(kind, position, this))
+            .collect();
+    }
+
+    fn create_handle(
+        &self,
+        kind: HandleKind,
+        position: LatLng,
+        this: &Weak<RefCell<Self>>,
+    ) -> EditHandle {
+        let class_name = match kind {
+            HandleKind::Vertex(_) => DRAW_HANDLE_CLASS_NAME.to_string(),
+            HandleKind::Midpoint(_) => {
+                format!("{DRAW_HANDLE_CLASS_NAME} {GHOST_HANDLE_CLASS_NAME}")
+            }
+        };
+        let marker = create_handle_marker(&self.map, position, &class_name);
+        let mut listeners = Vec::with_capacity(3);
+        listeners.push({
+            let this = Weak::clone(this);
+            let handle_marker = marker.clone();
+            EventListener::new(&marker, "drag", move |_: leaflet::Event| {
+                let Some(this) = this.upgrade() else {
+                    return;
+                };
+                let editor = this.borrow();
+                let points =
+                    edited_points(&editor.points, kind, handle_marker.get_lat_lng().into());
+                editor.polyline.set_lat_lngs(&lat_lng_array(&points));
+            })
+        });
+        listeners.push({
+            let this = Weak::clone(this);
+            let handle_marker = marker.clone();
+            EventListener::new(&marker, "dragend", move |_: leaflet::Event| {
+                let Some(this) = this.upgrade() else {
+                    return;
+                };
+                let position = handle_marker.get_lat_lng().into();
+                let edited_points = edited_points(&this.borrow().points, kind, position);
+                this.borrow_mut().reset(&this);
+                let thunk = Rc::clone(&this.borrow().thunk);
+                thunk.push_message(PointsEdited(edited_points));
+            })
+        });
+        if let HandleKind::Vertex(idx) = kind {
+            listeners.push({
+                let this = Weak::clone(this);
+                EventListener::new(&marker, "contextmenu", move |_: leaflet::Event| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    let (points, thunk) = {
+                        let editor = this.borrow();
+                        (
+                            removed_vertex(&editor.points, idx, editor.closed),
+                            Rc::clone(&editor.thunk),
+                        )
+                    };
+                    if let Some(points) = points {
+                        thunk.push_message(PointsEdited(points));
+                    }
+                })
+            });
+        }
+        EditHandle {
+            marker,
+            _listeners: listeners,
+        }
+    }
+
+    /// Show the current points until they have been changed by a rebuild.
+    fn reset(&mut self, this: &Rc<RefCell<Self>>) {
+        let points = std::mem::take(&mut self.points);
+        self.polyline.set_lat_lngs(&lat_lng_array(&points));
+        self.set_points(&points, &Rc::downgrade(this));
+    }
+
+    fn remove_handles(&mut self) {
+        for handle in self.handles.drain(..) {
+            handle.marker.remove();
+        }
+    }
+}
+
+impl<State, Action> View<State, Action, MapCtx, DynMessage> for Polyline<State>
+where
+    State: 'static,
+{
+    type Element = MapChildElement;
+
+    type ViewState = PolylineViewState;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        ctx.with_id(POLYLINE_ID, |ctx| {
+            let polyline = self.create_layer();
+            polyline.add_to(ctx.map());
+            let editor = self.editable.then(|| {
+                let editor =
+                    VertexEditor::new(ctx.map(), &polyline, ctx.message_thunk(), self.closed);
+                editor
+                    .borrow_mut()
+                    .set_points(&self.points, &Rc::downgrade(&editor));
+                editor
+            });
+            (
+                MapChildElement::Polyline(polyline),
+                PolylineViewState { editor },
+            )
+        })
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx,
+        e: Mut<Self::Element>,
+    ) {
+        ctx.with_id(POLYLINE_ID, |ctx| {
+            let Some(polyline) = e.as_polyline_mut() else {
+                log::error!("The element of a polyline is not a polyline: {e:?}");
+                return;
+            };
+            if prev.closed != self.closed {
+                polyline.remove();
+                *polyline = self.create_layer();
+                polyline.add_to(ctx.map());
+                // The editor refers to the previous layer.
+                if let Some(editor) = view_state.editor.take() {
+                    editor.borrow_mut().remove_handles();
+                }
+            } else if prev.points != self.points {
+                polyline.set_lat_lngs(&lat_lng_array(&self.points));
+            }
+            match (&view_state.editor, self.editable) {
+                (None, true) => {
+                    let editor =
+                        VertexEditor::new(ctx.map(), polyline, ctx.message_thunk(), self.closed);
+                    editor
+                        .borrow_mut()
+                        .set_points(&self.points, &Rc::downgrade(&editor));
+                    view_state.editor = Some(editor);
+                }
+                (Some(editor), true) => {
+                    if prev.points != self.points {
+                        editor
+                            .borrow_mut()
+                            .set_points(&self.points, &Rc::downgrade(editor));
+                    }
+                }
+                (Some(editor), false) => {
+                    editor.borrow_mut().remove_handles();
+                    view_state.editor = None;
+                }
+                (None, false) => {}
+            }
+        });
+    }
+
+    fn teardown(&self, view_state: &mut Self::ViewState, ctx: &mut MapCtx, e: Mut<Self::Element>) {
+        ctx.with_id(POLYLINE_ID, |_| {
+            if let Some(editor) = view_state.editor.take() {
+                editor.borrow_mut().remove_handles();
+            }
+            match e.as_polyline_mut() {
+                Some(polyline) => polyline.remove(),
+                None => log::error!("The element of a polyline is not a polyline: {e:?}"),
+            }
+        });
+    }
+
+    fn message(
+        &self,
+        _: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        state: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        debug_assert!(id_path.len() == 1 && id_path[0] == POLYLINE_ID);
+        let PointsEdited(points) = *message.downcast().unwrap_throw();
+        if let Some(on_edit) = &self.on_edit {
+            on_edit(state, points);
+        }
+        MessageResult::Nop
+    }
+}
@@ -0,0 +1,136 @@
+//! Editing the vertices of a [`polyline`](super::polyline), independent of the map and the browser.
+
+use crate::LatLng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum HandleKind {
+    Vertex(usize),
+    /// Between the vertex and the next one.
+    Midpoint(usize),
+}
+
+/// The handles of the vertices followed by the handles between them.
+pub(super) fn edit_handles(points: &[LatLng], closed: bool) -> Vec<(HandleKind, LatLng)> {
+    let vertices = points
+        .iter()
+        .enumerate()
+        .map(|(idx, position)| (HandleKind::Vertex(idx), *position));
+    let midpoints = (0..segment_count(points.len(), closed)).map(|idx| {
+        let from = points[idx];
+        let to = points[(idx + 1) % points.len()];
+        let position = LatLng::new((from.lat + to.lat) / 2.0, (from.lng + to.lng) / 2.0);
+        (HandleKind::Midpoint(idx), position)
+    });
+    vertices.chain(midpoints).collect()
+}
+
+/// The last vertex of a polygon is connected to the first one.
+const fn segment_count(point_count: usize, closed: bool) -> usize {
+    match point_count {
+        0 | 1 => 0,
+        n if closed => n,
+        n => n - 1,
+    }
+}
+
+/// The points after the handle has been dragged to the position.
+pub(super) fn edited_points(points: &[LatLng], kind: HandleKind, position: LatLng) -> Vec<LatLng> {
+    let mut points = points.to_vec();
+    match kind {
+        HandleKind::Vertex(idx) => points[idx] = position,
+        HandleKind::Midpoint(idx) => points.insert(idx + 1, position),
+    }
+    points
+}
+
+/// The points without the vertex or `None` if there would be too few points left.
+pub(super) fn removed_vertex(points: &[LatLng], idx: usize, closed: bool) -> Option<Vec<LatLng>> {
+    let min_points = if closed { 3 } else { 2 };
+    (idx < points.len() && points.len() > min_points).then(|| {
+        let mut points = points.to_vec();
+        points.remove(idx);
+        points
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: LatLng = LatLng::new(0.0, 0.0);
+    const B: LatLng = LatLng::new(0.0, 2.0);
+    const C: LatLng = LatLng::new(2.0, 2.0);
+
+    fn midpoints(points: &[LatLng], closed: bool) -> Vec<(usize, LatLng)> {
+        edit_handles(points, closed)
+            .into_iter()
+            .filter_map(|(kind, position)| match kind {
+                HandleKind::Vertex(_) => None,
+                HandleKind::Midpoint(idx) => Some((idx, position)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn midpoints_of_a_polyline() {
+        assert_eq!(
+            midpoints(&[A, B, C], false),
+            vec![(0, LatLng::new(0.0, 1.0)), (1, LatLng::new(1.0, 2.0))]
+        );
+        assert!(midpoints(&[A], false).is_empty());
+        assert!(midpoints(&[], true).is_empty());
+    }
+
+    #[test]
+    fn midpoints_of_a_polygon_wrap_around() {
+        assert_eq!(
+            midpoints(&[A, B, C], true),
+            vec![
+                (0, LatLng::new(0.0, 1.0)),
+                (1, LatLng::new(1.0, 2.0)),
+                (2, LatLng::new(1.0, 1.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn vertex_handles_come_first() {
+        let handles = edit_handles(&[A, B], false);
+        assert_eq!(
+            handles[..2],
+            [(HandleKind::Vertex(0), A), (HandleKind::Vertex(1), B)]
+        );
+    }
+
+    #[test]
+    fn drag_a_vertex() {
+        let position = LatLng::new(5.0, 5.0);
+        assert_eq!(
+            edited_points(&[A, B, C], HandleKind::Vertex(1), position),
+            vec![A, position, C]
+        );
+    }
+
+    #[test]
+    fn drag_a_midpoint_inserts_after_the_vertex() {
+        let position = LatLng::new(5.0, 5.0);
+        assert_eq!(
+            edited_points(&[A, B, C], HandleKind::Midpoint(0), position),
+            vec![A, position, B, C]
+        );
+        // The last segment of a polygon ends at the first vertex.
+        assert_eq!(
+            edited_points(&[A, B, C], HandleKind::Midpoint(2), position),
+            vec![A, B, C, position]
+        );
+    }
+
+    #[test]
+    fn remove_a_vertex_down_to_the_minimum() {
+        assert_eq!(removed_vertex(&[A, B, C], 1, false), Some(vec![A, C]));
+        assert_eq!(removed_vertex(&[A, B], 0, false), None);
+        assert_eq!(removed_vertex(&[A, B, C], 0, true), None);
+        assert_eq!(removed_vertex(&[A, B, C, A], 3, true), Some(vec![A, B, C]));
+        assert_eq!(removed_vertex(&[A, B, C], 3, false), None);
+    }
+}
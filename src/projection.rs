@@ -0,0 +1,345 @@
+//! Spherical (web) mercator projection (EPSG:3857) and tile math.
+//!
+//! The calculations match the ones of Leaflet
+//! (`L.CRS.EPSG3857` with 256 pixel tiles),
+//! but this module does not depend on Leaflet and can be used natively.
+
+use std::f64::consts::PI;
+
+use crate::{Bounds, LatLng, LatLngBounds, Point};
+
+/// Earth radius in meters used by the spherical mercator projection.
+pub const EARTH_RADIUS: f64 = 6_378_137.0;
+
+/// Latitude in degrees at which the projected world is square.
+pub const MAX_LATITUDE: f64 = 85.051_128_779_806_6;
+
+/// Size of a tile in pixels.
+pub const TILE_SIZE: f64 = 256.0;
+
+/// The coordinates of a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileCoords {
+    pub x: i32,
+    pub y: i32,
+    /// Zoom level
+    pub z: i32,
+}
+
+impl TileCoords {
+    /// The geographical area that is covered by the tile.
+    #[must_use]
+    pub fn bounds(&self) -> LatLngBounds {
+        let zoom = f64::from(self.z);
+        let top_left = Point::new(f64::from(self.x) * TILE_SIZE, f64::from(self.y) * TILE_SIZE);
+        let bottom_right = Point::new(top_left.x + TILE_SIZE, top_left.y + TILE_SIZE);
+        LatLngBounds::new(
+            pixel_to_lat_lng(top_left, zoom),
+            pixel_to_lat_lng(bottom_right, zoom),
+        )
+    }
+}
+
+/// A rectangular range of tiles at a zoom level (including `min` and `max`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileRange {
+    pub min: TileCoords,
+    pub max: TileCoords,
+}
+
+impl TileRange {
+    #[must_use]
+    pub fn len(&self) -> usize {
+        let columns = usize::try_from(self.max.x - self.min.x + 1).unwrap_or_default();
+        let rows = usize::try_from(self.max.y - self.min.y + 1).unwrap_or_default();
+        columns * rows
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// All tiles row by row.
+    pub fn tiles(&self) -> impl Iterator<Item = TileCoords> {
+        let Self { min, max } = *self;
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| TileCoords { x, y, z: min.z }))
+    }
+}
+
+/// Project geographical coordinates to meters.
+#[must_use]
+pub fn project(lat_lng: LatLng) -> Point {
+    let lat = lat_lng.lat.clamp(-MAX_LATITUDE, MAX_LATITUDE);
+    let sin = lat.to_radians().sin();
+    Point::new(
+        EARTH_RADIUS * lat_lng.lng.to_radians(),
+        EARTH_RADIUS * ((1.0 + sin) / (1.0 - sin)).ln() / 2.0,
+    )
+}
+
+/// Unproject meters to geographical coordinates.
+#[must_use]
+pub fn unproject(point: Point) -> LatLng {
+    LatLng::new(
+        (2.0 * (point.y / EARTH_RADIUS).exp().atan() - PI / 2.0).to_degrees(),
+        (point.x / EARTH_RADIUS).to_degrees(),
+    )
+}
+
+/// The size of the world in pixels at the given zoom level.
+#[must_use]
+pub fn scale(zoom: f64) -> f64 {
+    TILE_SIZE * zoom.exp2()
+}
+
+/// The zoom level at which the world has the given size in pixels.
+#[must_use]
+pub fn zoom(scale: f64) -> f64 {
+    (scale / TILE_SIZE).log2()
+}
+
+/// Pixel coordinates of a point at the given zoom level.
+#[must_use]
+pub fn lat_lng_to_pixel(lat_lng: LatLng, zoom: f64) -> Point {
+    let scale = scale(zoom);
+    Point::new(lng_x(lat_lng.lng) * scale, lat_y(lat_lng.lat) * scale)
+}
+
+/// Geographical coordinates of a pixel at the given zoom level.
+#[must_use]
+pub fn pixel_to_lat_lng(point: Point, zoom: f64) -> LatLng {
+    let scale = scale(zoom);
+    LatLng::new(y_lat(point.y / scale), x_lng(point.x / scale))
+}
+
+/// Pixel bounds of a geographical area at the given zoom level.
+#[must_use]
+pub fn pixel_bounds(bounds: &LatLngBounds, zoom: f64) -> Bounds {
+    Bounds::new(
+        lat_lng_to_pixel(bounds.south_west, zoom),
+        lat_lng_to_pixel(bounds.north_east, zoom),
+    )
+}
+
+/// The tiles that cover a geographical area at the given zoom level.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn tile_range(bounds: &LatLngBounds, zoom: i32) -> TileRange {
+    let pixel_bounds = pixel_bounds(bounds, f64::from(zoom));
+    let max_tile = f64::from(zoom).exp2() - 1.0;
+    let tile = |value: f64| (value.clamp(0.0, max_tile)) as i32;
+    TileRange {
+        min: TileCoords {
+            x: tile((pixel_bounds.min.x / TILE_SIZE).floor()),
+            y: tile((pixel_bounds.min.y / TILE_SIZE).floor()),
+            z: zoom,
+        },
+        max: TileCoords {
+            x: tile((pixel_bounds.max.x / TILE_SIZE).ceil() - 1.0),
+            y: tile((pixel_bounds.max.y / TILE_SIZE).ceil() - 1.0),
+            z: zoom,
+        },
+    }
+}
+
+/// The maximum zoom level at which the bounds fit into a viewport
+/// of the given size in pixels (like `map.getBoundsZoom` of Leaflet).
+///
+/// The zoom level is a multiple of `zoom_snap` (if it's greater than `0`).
+#[must_use]
+pub fn bounds_zoom(bounds: &LatLngBounds, size: Point, zoom_snap: f64) -> f64 {
+    let bounds_size = pixel_bounds(bounds, 0.0).size();
+    let scale_x = size.x / bounds_size.x;
+    let scale_y = size.y / bounds_size.y;
+    let zoom = zoom(TILE_SIZE * scale_x.min(scale_y));
+    if zoom_snap > 0.0 {
+        // Round to avoid floating point errors (as Leaflet does).
+        let zoom = (zoom / (zoom_snap / 100.0)).round() * (zoom_snap / 100.0);
+        (zoom / zoom_snap).floor() * zoom_snap
+    } else {
+        zoom
+    }
+}
+
+/// Ground resolution in meters per pixel at the given latitude and zoom level.
+#[must_use]
+pub fn meters_per_pixel(lat: f64, zoom: f64) -> f64 {
+    2.0 * PI * EARTH_RADIUS * lat.to_radians().cos() / scale(zoom)
+}
+
+/// Normalized x coordinate in the range `[0, 1]`.
+pub(crate) fn lng_x(lng: f64) -> f64 {
+    lng / 360.0 + 0.5
+}
+
+/// Normalized y coordinate in the range `[0, 1]`.
+pub(crate) fn lat_y(lat: f64) -> f64 {
+    let sin = lat.to_radians().sin();
+    let y = 0.5 - 0.25 * ((1.0 + sin) / (1.0 - sin)).ln() / PI;
+    y.clamp(0.0, 1.0)
+}
+
+pub(crate) fn x_lng(x: f64) -> f64 {
+    (x - 0.5) * 360.0
+}
+
+pub(crate) fn y_lat(y: f64) -> f64 {
+    let y2 = (180.0 - y * 360.0).to_radians();
+    360.0 / PI * y2.exp().atan() - 90.0
+}
+
+/// Wrap the longitude into the range `[-180, 180]`.
+pub(crate) fn wrap_lng(lng: f64) -> f64 {
+    if (-180.0..=180.0).contains(&lng) {
+        return lng;
+    }
+    (lng + 180.0).rem_euclid(360.0) - 180.0
+}
+
+#[cfg(test)]
+mod tests {
+    //! The expected values are the ones of `L.CRS.EPSG3857` in Leaflet 1.9.4.
+
+    use super::*;
+
+    /// The north-east corner of the projected world.
+    const NORTH_EAST: LatLng = LatLng::new(85.051_128_779_8, 180.0);
+
+    fn assert_point_near(actual: Point, expected: Point, tolerance: f64) {
+        assert!(
+            (actual.x - expected.x).abs() < tolerance && (actual.y - expected.y).abs() < tolerance,
+            "{actual:?} is not near {expected:?}"
+        );
+    }
+
+    fn assert_lat_lng_near(actual: LatLng, expected: LatLng) {
+        assert!(
+            (actual.lat - expected.lat).abs() < 1e-6 && (actual.lng - expected.lng).abs() < 1e-6,
+            "{actual:?} is not near {expected:?}"
+        );
+    }
+
+    #[test]
+    fn project_to_meters() {
+        assert_point_near(
+            project(LatLng::new(50.0, 30.0)),
+            Point::new(3_339_584.723_8, 6_446_275.841_02),
+            1e-3,
+        );
+        assert_point_near(
+            project(NORTH_EAST),
+            Point::new(20_037_508.342_79, 20_037_508.342_78),
+            1e-3,
+        );
+        // Clamped to the projected world
+        assert_point_near(
+            project(LatLng::new(-90.0, -180.0)),
+            Point::new(-20_037_508.342_79, -20_037_508.342_78),
+            1e-3,
+        );
+    }
+
+    #[test]
+    fn unproject_from_meters() {
+        assert_lat_lng_near(
+            unproject(Point::new(3_339_584.723_8, 6_446_275.841_02)),
+            LatLng::new(50.0, 30.0),
+        );
+        assert_lat_lng_near(
+            unproject(Point::new(20_037_508.342_79, 20_037_508.342_78)),
+            NORTH_EAST,
+        );
+    }
+
+    #[test]
+    fn lat_lng_to_pixels() {
+        assert_point_near(
+            lat_lng_to_pixel(LatLng::new(0.0, 0.0), 0.0),
+            Point::new(128.0, 128.0),
+            1e-6,
+        );
+        assert_point_near(
+            lat_lng_to_pixel(NORTH_EAST, 0.0),
+            Point::new(256.0, 0.0),
+            1e-6,
+        );
+        assert_point_near(
+            lat_lng_to_pixel(LatLng::new(0.0, -90.0), 2.0),
+            Point::new(256.0, 512.0),
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn pixels_to_lat_lng() {
+        assert_lat_lng_near(
+            pixel_to_lat_lng(Point::new(128.0, 128.0), 0.0),
+            LatLng::new(0.0, 0.0),
+        );
+        assert_lat_lng_near(pixel_to_lat_lng(Point::new(256.0, 0.0), 0.0), NORTH_EAST);
+        let london = LatLng::new(51.505, -0.09);
+        assert_lat_lng_near(
+            pixel_to_lat_lng(lat_lng_to_pixel(london, 13.5), 13.5),
+            london,
+        );
+    }
+
+    #[test]
+    fn scale_and_zoom() {
+        assert!((scale(0.0) - 256.0).abs() < f64::EPSILON);
+        assert!((scale(3.0) - 2048.0).abs() < f64::EPSILON);
+        assert!((zoom(2048.0) - 3.0).abs() < f64::EPSILON);
+        assert!((zoom(scale(2.5)) - 2.5).abs() < 1e-12);
+        assert!((meters_per_pixel(0.0, 0.0) - 156_543.033_928).abs() < 1e-6);
+        assert!((meters_per_pixel(60.0, 1.0) - 39_135.758_482).abs() < 1e-6);
+    }
+
+    #[test]
+    fn zoom_to_fit_bounds() {
+        let world = LatLngBounds::new(
+            LatLng::new(-NORTH_EAST.lat, -180.0),
+            LatLng::new(NORTH_EAST.lat, 180.0),
+        );
+        let zoom = |size: (f64, f64), zoom_snap| bounds_zoom(&world, size.into(), zoom_snap);
+        assert!(zoom((256.0, 256.0), 1.0).abs() < 1e-9);
+        assert!((zoom((512.0, 1024.0), 1.0) - 1.0).abs() < 1e-9);
+        // 600 / 256 pixels = 2^1.2288
+        assert!((zoom((1000.0, 600.0), 0.0) - 1.228_818_690_495_881).abs() < 1e-9);
+        assert!((zoom((1000.0, 600.0), 1.0) - 1.0).abs() < 1e-9);
+        assert!((zoom((1000.0, 600.0), 0.25) - 1.0).abs() < 1e-9);
+        assert!((zoom((1000.0, 600.0), 0.1) - 1.2).abs() < 1e-9);
+        // Rounded before snapping (like `map.getBoundsZoom`)
+        let quarter = LatLngBounds::new(LatLng::new(0.0, 0.0), LatLng::new(0.0, 90.0));
+        let zoom = bounds_zoom(&quarter, Point::new(256.0 - 1e-9, 256.0), 1.0);
+        assert!((zoom - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tiles() {
+        let london = LatLng::new(51.505, -0.09);
+        let range = tile_range(&LatLngBounds::new(london, london), 10);
+        let tile = TileCoords {
+            x: 511,
+            y: 340,
+            z: 10,
+        };
+        assert_eq!(range.tiles().collect::<Vec<_>>(), [tile]);
+        assert!(tile.bounds().contains(london));
+        let world = tile_range(
+            &LatLngBounds::new(LatLng::new(-89.0, -200.0), NORTH_EAST),
+            2,
+        );
+        assert_eq!(world.len(), 16);
+    }
+
+    #[test]
+    fn wrap_longitude() {
+        assert!((wrap_lng(190.0) + 170.0).abs() < f64::EPSILON);
+        assert!(wrap_lng(360.0).abs() < f64::EPSILON);
+        assert!((wrap_lng(-190.0) - 170.0).abs() < f64::EPSILON);
+        assert!((wrap_lng(180.0) - 180.0).abs() < f64::EPSILON);
+        assert!((wrap_lng(-180.0) + 180.0).abs() < f64::EPSILON);
+    }
+}
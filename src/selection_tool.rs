@@ -0,0 +1,452 @@
+use std::{cell::RefCell, hash::Hash, marker::PhantomData, rc::Rc};
+
+use web_sys::{
+    js_sys::Array,
+    wasm_bindgen::{JsValue, UnwrapThrowExt as _},
+};
+use xilem_web::{
+    core::{AnyView, MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker as _},
+    DynMessage,
+};
+
+use crate::{
+    geometry::lat_lng_array,
+    keyed::KeyedViewState,
+    map::{handler_enabled, set_handler_enabled, DomEventListener, EventListener},
+    AnyMapView, Either, Keyed, LatLng, LatLngBounds, MapChildElement, MapCtx, Shape,
+};
+
+/// Distinctive ID for better debugging
+const SELECTION_TOOL_ID: ViewId = ViewId::new(23674);
+
+/// The children are wrapped with their own ID
+/// to distinguish their messages from the ones of the selection tool.
+const SELECTION_CHILDREN_ID: ViewId = ViewId::new(23675);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelectionMode {
+    /// Select within a rectangle.
+    #[default]
+    Box,
+    /// Select within a freehand polygon.
+    Lasso,
+}
+
+impl SelectionMode {
+    /// The selection that is spanned by the dragged points.
+    fn shape(self, points: &[LatLng]) -> Option<Shape> {
+        match (self, points) {
+            (Self::Box, [first, .., last]) => {
+                Some(Shape::Rectangle(LatLngBounds::new(*first, *last)))
+            }
+            (Self::Lasso, points) if points.len() > 2 => Some(Shape::Polygon(points.to_vec())),
+            _ => None,
+        }
+    }
+}
+
+/// A map child that can be selected with the [`selection_tool`]
+/// (e.g. a [`marker`](crate::marker) or a [`polyline`](crate::polyline)).
+pub trait Selectable {
+    /// The points that must be within the selection.
+    fn points(&self) -> &[LatLng];
+}
+
+impl<L: Selectable, R: Selectable> Selectable for Either<L, R> {
+    fn points(&self) -> &[LatLng] {
+        match self {
+            Self::Left(view) => view.points(),
+            Self::Right(view) => view.points(),
+        }
+    }
+}
+
+/// Nothing is shown and selected.
+impl<V: Selectable> Selectable for Option<V> {
+    fn points(&self) -> &[LatLng] {
+        match self {
+            Some(view) => view.points(),
+            None => &[],
+        }
+    }
+}
+
+impl<V: Selectable + ?Sized> Selectable for Box<V> {
+    fn points(&self) -> &[LatLng] {
+        (**self).points()
+    }
+}
+
+/// A type-erased [`Selectable`] map child
+/// (like [`AnyMapView`] for the children of the [`selection_tool`]).
+///
+/// It is used boxed (i.e. `Box<dyn SelectableMapView<State>>`).
+pub trait SelectableMapView<State, Action = ()>:
+    AnyView<State, Action, MapCtx, MapChildElement, DynMessage> + Selectable
+{
+    #[doc(hidden)]
+    fn as_any_map_view(&self) -> &AnyMapView<State, Action>;
+}
+
+impl<State, Action, V> SelectableMapView<State, Action> for V
+where
+    State: 'static,
+    Action: 'static,
+    V: View<State, Action, MapCtx, DynMessage, Element = MapChildElement> + Selectable,
+{
+    fn as_any_map_view(&self) -> &AnyMapView<State, Action> {
+        self
+    }
+}
+
+impl<State, Action> ViewMarker for dyn SelectableMapView<State, Action> {}
+
+impl<State, Action> View<State, Action, MapCtx, DynMessage> for dyn SelectableMapView<State, Action>
+where
+    State: 'static,
+    Action: 'static,
+{
+    type Element = MapChildElement;
+
+    type ViewState =
+        <AnyMapView<State, Action> as View<State, Action, MapCtx, DynMessage>>::ViewState;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        self.as_any_map_view().build(ctx)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx,
+        element: Mut<Self::Element>,
+    ) {
+        self.as_any_map_view()
+            .rebuild(prev.as_any_map_view(), view_state, ctx, element);
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx,
+        element: Mut<Self::Element>,
+    ) {
+        self.as_any_map_view().teardown(view_state, ctx, element);
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        state: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        self.as_any_map_view()
+            .message(view_state, id_path, message, state)
+    }
+}
+
+/// Select keyed children by dragging with the shift key pressed.
+///
+/// The keys of all children whose points (see [`Selectable`]) are completely within
+/// the selection are reported to `on_select`.
+///
+/// The box zoom of the map (which also uses the shift key) is disabled.
+pub fn selection_tool<State, K, V, F>(
+    mode: SelectionMode,
+    children: Keyed<K, V>,
+    on_select: F,
+) -> SelectionTool<State, K, V, F>
+where
+    F: Fn(&mut State, Vec<K>) + 'static,
+{
+    SelectionTool {
+        mode,
+        children,
+        on_select,
+        phantom: PhantomData,
+    }
+}
+
+pub struct SelectionTool<State, K, V, F> {
+    mode: SelectionMode,
+    children: Keyed<K, V>,
+    on_select: F,
+    phantom: PhantomData<fn() -> State>,
+}
+
+impl<State, K, V, F> ViewMarker for SelectionTool<State, K, V, F> {}
+
+#[derive(Debug)]
+struct SelectionMessage(Shape);
+
+pub struct SelectionToolViewState<K, S> {
+    children: KeyedViewState<K, S>,
+    layer: Rc<RefCell<SelectionLayer>>,
+    listeners: Vec<EventListener<leaflet::Map>>,
+    /// The mouse might be released outside of the map.
+    on_document_mouse_up: Option<DomEventListener>,
+    /// Whether the box zoom was enabled before the selection tool has disabled it.
+    box_zoom_enabled: bool,
+}
+
+struct SelectionLayer {
+    map: leaflet::Map,
+    mode: SelectionMode,
+    /// Preview of the selection
+    sketch: leaflet::Polyline,
+    /// The corners of the box or the points of the lasso
+    /// (empty if nothing is selected at the moment).
+    points: Vec<LatLng>,
+    /// Whether the dragging was enabled before the selection started.
+    dragging_enabled: bool,
+}
+
+impl SelectionLayer {
+    fn shape(&self) -> Option<Shape> {
+        self.mode.shape(&self.points)
+    }
+
+    fn draw(&self) {
+        let outline = self
+            .shape()
+            .map(|shape| shape.outline())
+            .unwrap_or_default();
+        self.sketch.set_lat_lngs(&lat_lng_array(&outline));
+    }
+
+    fn start(&mut self, position: LatLng) {
+        // Don't move the map while selecting.
+        self.dragging_enabled = handler_enabled(&self.map, "dragging");
+        set_handler_enabled(&self.map, "dragging", false);
+        self.points = vec![position];
+    }
+
+    fn extend(&mut self, position: LatLng) {
+        if self.points.is_empty() {
+            return;
+        }
+        match self.mode {
+            SelectionMode::Box => {
+                self.points.truncate(1);
+                self.points.push(position);
+            }
+            SelectionMode::Lasso => self.points.push(position),
+        }
+        self.draw();
+    }
+
+    fn finish(&mut self) -> Option<Shape> {
+        if self.points.is_empty() {
+            return None;
+        }
+        if self.dragging_enabled {
+            set_handler_enabled(&self.map, "dragging", true);
+        }
+        let shape = self.shape();
+        self.points.clear();
+        self.draw();
+        shape
+    }
+}
+
+impl<State, Action, K, V, F> View<State, Action, MapCtx, DynMessage>
+    for SelectionTool<State, K, V, F>
+where
+    State: 'static,
+    Action: 'static,
+    K: Hash + Eq + Clone + 'static,
+    V: View<State, Action, MapCtx, DynMessage, Element = MapChildElement> + Selectable,
+    F: Fn(&mut State, Vec<K>) + 'static,
+{
+    type Element = MapChildElement;
+
+    type ViewState = SelectionToolViewState<K, V::ViewState>;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        let (element, children) = ctx.with_id(SELECTION_CHILDREN_ID, |ctx| {
+            View::<State, Action, MapCtx, _>::build(&self.children, ctx)
+        });
+        ctx.with_id(SELECTION_TOOL_ID, |ctx| {
+            let map = ctx.map();
+            let box_zoom_enabled = handler_enabled(map, "boxZoom");
+            set_handler_enabled(map, "boxZoom", false);
+            let sketch = leaflet::Polyline::new(&Array::new());
+            sketch.add_to(map);
+            let layer = Rc::new(RefCell::new(SelectionLayer {
+                map: map.clone(),
+                mode: self.mode,
+                sketch,
+                points: Vec::new(),
+                dragging_enabled: false,
+            }));
+            let finish = {
+                let layer = Rc::clone(&layer);
+                let thunk = ctx.message_thunk();
+                Rc::new(move || {
+                    // The borrow must be released before the message is handled.
+                    let shape = layer.borrow_mut().finish();
+                    if let Some(shape) = shape {
+                        thunk.push_message(SelectionMessage(shape));
+                    }
+                })
+            };
+            let listeners = vec![
+                {
+                    let layer = Rc::clone(&layer);
+                    EventListener::new(map, "mousedown", move |ev: leaflet::MouseEvent| {
+                        if ev.original_event().shift_key() {
+                            layer.borrow_mut().start(ev.lat_lng().into());
+                        }
+                    })
+                },
+                {
+                    let layer = Rc::clone(&layer);
+                    EventListener::new(map, "mousemove", move |ev: leaflet::MouseEvent| {
+                        layer.borrow_mut().extend(ev.lat_lng().into());
+                    })
+                },
+                {
+                    let finish = Rc::clone(&finish);
+                    EventListener::new(map, "mouseup", move |_: leaflet::MouseEvent| finish())
+                },
+            ];
+            let on_document_mouse_up =
+                DomEventListener::new(&xilem_web::document(), "mouseup", move |_: JsValue| {
+                    finish();
+                });
+            let view_state = SelectionToolViewState {
+                children,
+                layer,
+                listeners,
+                on_document_mouse_up: Some(on_document_mouse_up),
+                box_zoom_enabled,
+            };
+            (element, view_state)
+        })
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx,
+        element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(SELECTION_CHILDREN_ID, |ctx| {
+            View::<State, Action, MapCtx, _>::rebuild(
+                &self.children,
+                &prev.children,
+                &mut view_state.children,
+                ctx,
+                element,
+            );
+        });
+        if prev.mode != self.mode {
+            let mut layer = view_state.layer.borrow_mut();
+            layer.finish();
+            layer.mode = self.mode;
+        }
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx,
+        element: Mut<Self::Element>,
+    ) {
+        view_state.listeners.clear();
+        view_state.on_document_mouse_up = None;
+        {
+            let mut layer = view_state.layer.borrow_mut();
+            layer.finish();
+            layer.sketch.remove();
+            if view_state.box_zoom_enabled {
+                set_handler_enabled(&layer.map, "boxZoom", true);
+            }
+        }
+        ctx.with_id(SELECTION_CHILDREN_ID, |ctx| {
+            View::<State, Action, MapCtx, _>::teardown(
+                &self.children,
+                &mut view_state.children,
+                ctx,
+                element,
+            );
+        });
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        state: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        match id_path.split_first() {
+            Some((first, rest)) if *first == SELECTION_CHILDREN_ID => {
+                View::<State, Action, MapCtx, _>::message(
+                    &self.children,
+                    &mut view_state.children,
+                    rest,
+                    message,
+                    state,
+                )
+            }
+            Some((first, [])) if *first == SELECTION_TOOL_ID => {
+                let SelectionMessage(shape) = *message.downcast().unwrap_throw();
+                let selected = self
+                    .children
+                    .children()
+                    .iter()
+                    .filter(|(_, child)| {
+                        let points = child.points();
+                        !points.is_empty() && points.iter().all(|point| shape.contains(*point))
+                    })
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                (self.on_select)(state, selected);
+                MessageResult::Nop
+            }
+            _ => MessageResult::Stale(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{marker, Marker};
+
+    const A: LatLng = LatLng::new(0.0, 0.0);
+    const B: LatLng = LatLng::new(1.0, 2.0);
+    const C: LatLng = LatLng::new(2.0, 1.0);
+
+    #[test]
+    fn box_is_spanned_by_the_first_and_the_last_point() {
+        assert_eq!(SelectionMode::Box.shape(&[]), None);
+        assert_eq!(SelectionMode::Box.shape(&[A]), None);
+        assert_eq!(
+            SelectionMode::Box.shape(&[C, B, A]),
+            Some(Shape::Rectangle(LatLngBounds::new(A, C)))
+        );
+    }
+
+    #[test]
+    fn points_of_wrapped_children() {
+        assert_eq!(Either::<_, Marker>::Left(marker(A)).points(), [A]);
+        assert_eq!(Some(marker(B)).points(), [B]);
+        assert!(None::<Marker>.points().is_empty());
+        let boxed: Box<dyn SelectableMapView<()>> = Box::new(marker(C));
+        assert_eq!(boxed.points(), [C]);
+    }
+
+    #[test]
+    fn lasso_is_a_polygon_of_all_points() {
+        assert_eq!(SelectionMode::Lasso.shape(&[A, B]), None);
+        assert_eq!(
+            SelectionMode::Lasso.shape(&[A, B, C]),
+            Some(Shape::Polygon(vec![A, B, C]))
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Editable shapes.
+//!
+//! This module does not depend on Leaflet and can be used natively.
+
+use crate::{
+    measure::{destination, haversine_distance},
+    LatLng, LatLngBounds,
+};
+
+/// Number of segments of the outline of a circle.
+const CIRCLE_SEGMENTS: u32 = 64;
+
+/// A geometry that can be drawn on a map.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Shape {
+    Marker(LatLng),
+    Polyline(Vec<LatLng>),
+    /// A polygon that is closed implicitly.
+    Polygon(Vec<LatLng>),
+    Rectangle(LatLngBounds),
+    Circle {
+        center: LatLng,
+        /// Radius in meters.
+        radius: f64,
+    },
+}
+
+impl Shape {
+    /// The points that can be moved to edit the shape.
+    ///
+    /// A rectangle has its corners (counterclockwise starting at south-west)
+    /// and a circle has its center and a point on its circumference.
+    #[must_use]
+    pub fn handles(&self) -> Vec<LatLng> {
+        match self {
+            Self::Marker(position) => vec![*position],
+            Self::Polyline(points) | Self::Polygon(points) => points.clone(),
+            Self::Rectangle(bounds) => corners(bounds).to_vec(),
+            Self::Circle { center, radius } => vec![*center, destination(*center, 90.0, *radius)],
+        }
+    }
+
+    /// Move the handle with the given index (see [`Shape::handles`]).
+    ///
+    /// Nothing happens if there is no such handle.
+    pub fn move_handle(&mut self, index: usize, position: LatLng) {
+        match self {
+            Self::Marker(marker_position) => {
+                if index == 0 {
+                    *marker_position = position;
+                }
+            }
+            Self::Polyline(points) | Self::Polygon(points) => {
+                if let Some(point) = points.get_mut(index) {
+                    *point = position;
+                }
+            }
+            Self::Rectangle(bounds) => {
+                if index < 4 {
+                    let opposite = corners(bounds)[(index + 2) % 4];
+                    *bounds = LatLngBounds::new(position, opposite);
+                }
+            }
+            Self::Circle { center, radius } => match index {
+                0 => *center = position,
+                1 => *radius = haversine_distance(*center, position),
+                _ => {}
+            },
+        }
+    }
+
+    /// Check whether the point is within the area of the shape.
+    ///
+    /// Markers and polylines do not have an area and contain no points.
+    #[must_use]
+    pub fn contains(&self, point: LatLng) -> bool {
+        match self {
+            Self::Marker(_) | Self::Polyline(_) => false,
+            Self::Polygon(points) => polygon_contains(points, point),
+            Self::Rectangle(bounds) => bounds.contains(point),
+            Self::Circle { center, radius } => haversine_distance(*center, point) <= *radius,
+        }
+    }
+
+    /// The outline of the shape (closed for areas, approximated for circles).
+    #[must_use]
+    pub fn outline(&self) -> Vec<LatLng> {
+        match self {
+            Self::Marker(position) => vec![*position],
+            Self::Polyline(points) => points.clone(),
+            Self::Polygon(points) => close(points.clone()),
+            Self::Rectangle(bounds) => close(corners(bounds).to_vec()),
+            Self::Circle { center, radius } => close(
+                (0..CIRCLE_SEGMENTS)
+                    .map(|i| {
+                        let bearing = f64::from(i) * 360.0 / f64::from(CIRCLE_SEGMENTS);
+                        destination(*center, bearing, *radius)
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+const fn corners(bounds: &LatLngBounds) -> [LatLng; 4] {
+    [
+        bounds.south_west,
+        LatLng::new(bounds.south(), bounds.east()),
+        bounds.north_east,
+        LatLng::new(bounds.north(), bounds.west()),
+    ]
+}
+
+fn close(mut points: Vec<LatLng>) -> Vec<LatLng> {
+    if let Some(&first) = points.first() {
+        points.push(first);
+    }
+    points
+}
+
+/// Even-odd rule (ray casting) in geographical coordinates.
+fn polygon_contains(points: &[LatLng], point: LatLng) -> bool {
+    let mut inside = false;
+    let Some(mut prev) = points.last() else {
+        return false;
+    };
+    for current in points {
+        if (current.lat > point.lat) != (prev.lat > point.lat)
+            && point.lng
+                < (prev.lng - current.lng) * (point.lat - current.lat) / (prev.lat - current.lat)
+                    + current.lng
+        {
+            inside = !inside;
+        }
+        prev = current;
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn bounds() -> LatLngBounds {
+        LatLngBounds::new(LatLng::new(0.0, 0.0), LatLng::new(2.0, 4.0))
+    }
+
+    /// A concave polygon (U shape) that is open at the north.
+    fn u_shape() -> Vec<LatLng> {
+        vec![
+            LatLng::new(0.0, 0.0),
+            LatLng::new(0.0, 3.0),
+            LatLng::new(3.0, 3.0),
+            LatLng::new(3.0, 2.0),
+            LatLng::new(1.0, 2.0),
+            LatLng::new(1.0, 1.0),
+            LatLng::new(3.0, 1.0),
+            LatLng::new(3.0, 0.0),
+        ]
+    }
+
+    #[test]
+    fn concave_polygon_contains() {
+        let points = u_shape();
+        assert!(polygon_contains(&points, LatLng::new(0.5, 1.5)));
+        assert!(polygon_contains(&points, LatLng::new(2.0, 0.5)));
+        assert!(polygon_contains(&points, LatLng::new(2.0, 2.5)));
+        // The gap of the U
+        assert!(!polygon_contains(&points, LatLng::new(2.0, 1.5)));
+        assert!(!polygon_contains(&points, LatLng::new(-1.0, 1.5)));
+        assert!(!polygon_contains(&[], LatLng::new(0.0, 0.0)));
+    }
+
+    #[test]
+    fn move_rectangle_corner() {
+        let mut shape = Shape::Rectangle(bounds());
+        // The north-east corner
+        shape.move_handle(2, LatLng::new(5.0, 6.0));
+        assert_eq!(
+            shape,
+            Shape::Rectangle(LatLngBounds::new(
+                LatLng::new(0.0, 0.0),
+                LatLng::new(5.0, 6.0)
+            ))
+        );
+        // The south-east corner beyond the opposite one flips the rectangle.
+        shape.move_handle(1, LatLng::new(6.0, -1.0));
+        assert_eq!(
+            shape,
+            Shape::Rectangle(LatLngBounds::new(
+                LatLng::new(5.0, -1.0),
+                LatLng::new(6.0, 0.0)
+            ))
+        );
+        let before = shape.clone();
+        shape.move_handle(4, LatLng::new(1.0, 1.0));
+        assert_eq!(shape, before);
+    }
+
+    #[test]
+    fn move_circle_handles() {
+        let center = LatLng::new(10.0, 10.0);
+        let mut shape = Shape::Circle {
+            center,
+            radius: 1000.0,
+        };
+        let handles = shape.handles();
+        assert_eq!(handles[0], center);
+        assert!((haversine_distance(center, handles[1]) - 1000.0).abs() < EPSILON * 1e3);
+
+        let on_circumference = destination(center, 0.0, 2500.0);
+        shape.move_handle(1, on_circumference);
+        let Shape::Circle { radius, .. } = shape else {
+            unreachable!();
+        };
+        assert!((radius - 2500.0).abs() < 1e-6);
+
+        let moved = LatLng::new(20.0, 20.0);
+        shape.move_handle(0, moved);
+        assert_eq!(
+            shape,
+            Shape::Circle {
+                center: moved,
+                radius
+            }
+        );
+    }
+
+    #[test]
+    fn contains() {
+        let point = LatLng::new(1.0, 1.0);
+        assert!(!Shape::Marker(point).contains(point));
+        assert!(
+            !Shape::Polyline(vec![LatLng::new(0.0, 0.0), LatLng::new(2.0, 2.0)]).contains(point)
+        );
+        assert!(Shape::Polygon(u_shape()).contains(LatLng::new(0.5, 0.5)));
+        assert!(Shape::Rectangle(bounds()).contains(point));
+        assert!(!Shape::Rectangle(bounds()).contains(LatLng::new(3.0, 1.0)));
+        let circle = Shape::Circle {
+            center: LatLng::new(0.0, 0.0),
+            radius: 200_000.0,
+        };
+        assert!(circle.contains(point));
+        assert!(!circle.contains(LatLng::new(2.0, 0.0)));
+    }
+
+    #[test]
+    fn outline_is_closed_for_areas() {
+        let points = u_shape();
+        let outline = Shape::Polygon(points.clone()).outline();
+        assert_eq!(outline.len(), points.len() + 1);
+        assert_eq!(outline.first(), outline.last());
+
+        assert_eq!(Shape::Polyline(points.clone()).outline(), points);
+
+        let rectangle = Shape::Rectangle(bounds()).outline();
+        assert_eq!(
+            rectangle,
+            vec![
+                LatLng::new(0.0, 0.0),
+                LatLng::new(0.0, 4.0),
+                LatLng::new(2.0, 4.0),
+                LatLng::new(2.0, 0.0),
+                LatLng::new(0.0, 0.0),
+            ]
+        );
+
+        let center = LatLng::new(45.0, 7.0);
+        let circle = Shape::Circle {
+            center,
+            radius: 500.0,
+        }
+        .outline();
+        assert_eq!(circle.len(), CIRCLE_SEGMENTS as usize + 1);
+        assert_eq!(circle.first(), circle.last());
+        for point in circle {
+            assert!((haversine_distance(center, point) - 500.0).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Spatial index to find the items within an area.
+//!
+//! This module does not depend on Leaflet and can be used natively.
+
+use rstar::{
+    primitives::{GeomWithData, Rectangle},
+    RTree, AABB,
+};
+
+use crate::{projection::wrap_lng, LatLng, LatLngBounds, Shape};
+
+/// An item with a geographical extent.
+pub trait Spatial {
+    /// The smallest bounds that contain the item
+    /// (`None` if the item has no position, e.g. an empty polyline).
+    fn bounds(&self) -> Option<LatLngBounds>;
+}
+
+impl Spatial for LatLng {
+    fn bounds(&self) -> Option<LatLngBounds> {
+        Some(LatLngBounds::new(*self, *self))
+    }
+}
+
+impl Spatial for LatLngBounds {
+    fn bounds(&self) -> Option<LatLngBounds> {
+        Some(*self)
+    }
+}
+
+impl Spatial for Shape {
+    fn bounds(&self) -> Option<LatLngBounds> {
+        LatLngBounds::from_points(self.outline())
+    }
+}
+
+type IndexEntry = GeomWithData<Rectangle<[f64; 2]>, usize>;
+
+/// An R-tree of the bounds of items (referenced by their index).
+///
+/// Items without bounds are not indexed and are never found.
+pub struct SpatialIndex {
+    tree: RTree<IndexEntry>,
+}
+
+impl SpatialIndex {
+    #[must_use]
+    pub fn new<'a, T>(items: impl IntoIterator<Item = &'a T>) -> Self
+    where
+        T: Spatial + 'a,
+    {
+        let entries = items
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, item)| {
+                let bounds = item.bounds()?;
+                let rectangle = Rectangle::from_corners(
+                    [bounds.west(), bounds.south()],
+                    [bounds.east(), bounds.north()],
+                );
+                Some(GeomWithData::new(rectangle, idx))
+            })
+            .collect();
+        Self {
+            tree: RTree::bulk_load(entries),
+        }
+    }
+
+    /// The number of indexed items.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The sorted indices of all items that intersect the bounds.
+    ///
+    /// Bounds that cross the antimeridian (i.e. that extend beyond
+    /// a longitude of 180 degrees) are wrapped.
+    #[must_use]
+    pub fn query(&self, bounds: &LatLngBounds) -> Vec<usize> {
+        let (west, east) = (bounds.west(), bounds.east());
+        let lng_ranges = if east - west >= 360.0 {
+            [(-180.0, 180.0), (1.0, 0.0)]
+        } else {
+            let (west, east) = (wrap_lng(west), wrap_lng(east));
+            if west <= east {
+                [(west, east), (1.0, 0.0)]
+            } else {
+                [(west, 180.0), (-180.0, east)]
+            }
+        };
+        let mut indices: Vec<_> = lng_ranges
+            .into_iter()
+            .filter(|(west, east)| west <= east)
+            .flat_map(|(west, east)| {
+                let envelope = AABB::from_corners([west, bounds.south()], [east, bounds.north()]);
+                self.tree
+                    .locate_in_envelope_intersecting(&envelope)
+                    .map(|entry| entry.data)
+            })
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+}
+
+/// The sorted indices of the items within the visible bounds
+/// extended by the margin (see [`pad_bounds`]).
+///
+/// This is the culling of the [`viewport_culled`](crate::viewport_culled) view.
+#[must_use]
+pub fn visible_items(index: &SpatialIndex, bounds: &LatLngBounds, margin: f64) -> Vec<usize> {
+    index.query(&pad_bounds(bounds, margin))
+}
+
+/// Extend the bounds by a part of their size on each side
+/// (e.g. `0.5` results in bounds that are twice as wide and high).
+#[must_use]
+pub fn pad_bounds(bounds: &LatLngBounds, ratio: f64) -> LatLngBounds {
+    let lat_padding = (bounds.north() - bounds.south()) * ratio;
+    let lng_padding = (bounds.east() - bounds.west()) * ratio;
+    LatLngBounds::new(
+        LatLng::new(
+            (bounds.south() - lat_padding).max(-90.0),
+            bounds.west() - lng_padding,
+        ),
+        LatLng::new(
+            (bounds.north() + lat_padding).min(90.0),
+            bounds.east() + lng_padding,
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(south: f64, west: f64, north: f64, east: f64) -> LatLngBounds {
+        LatLngBounds::new(LatLng::new(south, west), LatLng::new(north, east))
+    }
+
+    #[test]
+    fn query_points_and_bounds() {
+        let items = [
+            bounds(0.0, 0.0, 0.0, 0.0),
+            bounds(5.0, 5.0, 15.0, 15.0),
+            bounds(20.0, 20.0, 20.0, 20.0),
+            bounds(-10.0, -10.0, -5.0, -5.0),
+        ];
+        let index = SpatialIndex::new(&items);
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.query(&bounds(-1.0, -1.0, 10.0, 10.0)), [0, 1]);
+        // Touching the edge
+        assert_eq!(index.query(&bounds(15.0, 15.0, 20.0, 20.0)), [1, 2]);
+        assert!(index.query(&bounds(30.0, 30.0, 40.0, 40.0)).is_empty());
+    }
+
+    #[test]
+    fn skip_items_without_bounds() {
+        let items = [
+            Shape::Polyline(Vec::new()),
+            Shape::Marker(LatLng::new(1.0, 1.0)),
+        ];
+        let index = SpatialIndex::new(&items);
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.query(&bounds(-90.0, -180.0, 90.0, 180.0)), [1]);
+    }
+
+    #[test]
+    fn empty_index() {
+        let index = SpatialIndex::new::<LatLng>([]);
+        assert!(index.is_empty());
+        assert!(index.query(&bounds(-90.0, -180.0, 90.0, 180.0)).is_empty());
+        assert!(visible_items(&index, &bounds(0.0, 0.0, 1.0, 1.0), 0.5).is_empty());
+    }
+
+    #[test]
+    fn query_across_the_antimeridian() {
+        let items = [
+            LatLng::new(0.0, 179.0),
+            LatLng::new(0.0, -179.0),
+            LatLng::new(0.0, 0.0),
+        ];
+        let index = SpatialIndex::new(&items);
+        // As Leaflet reports the bounds east of the antimeridian
+        assert_eq!(index.query(&bounds(-1.0, 170.0, 1.0, 190.0)), [0, 1]);
+        assert_eq!(index.query(&bounds(-1.0, -190.0, 1.0, -170.0)), [0, 1]);
+        // More than the whole world
+        assert_eq!(index.query(&bounds(-1.0, -200.0, 1.0, 300.0)), [0, 1, 2]);
+    }
+
+    #[test]
+    fn pad_with_the_margin() {
+        let padded = pad_bounds(&bounds(0.0, 0.0, 10.0, 20.0), 0.5);
+        assert_eq!(padded, bounds(-5.0, -10.0, 15.0, 30.0));
+        // The latitude is clamped
+        let padded = pad_bounds(&bounds(40.0, 0.0, 80.0, 10.0), 1.0);
+        assert_eq!(padded, bounds(0.0, -10.0, 90.0, 20.0));
+    }
+
+    #[test]
+    fn visible_items_within_the_margin() {
+        let items = [
+            LatLng::new(5.0, 5.0),
+            LatLng::new(12.0, 5.0),
+            LatLng::new(5.0, -8.0),
+            LatLng::new(30.0, 30.0),
+        ];
+        let index = SpatialIndex::new(&items);
+        let visible = bounds(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(visible_items(&index, &visible, 0.0), [0]);
+        assert_eq!(visible_items(&index, &visible, 0.5), [0, 1]);
+        assert_eq!(visible_items(&index, &visible, 1.0), [0, 1, 2]);
+    }
+}
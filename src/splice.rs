@@ -0,0 +1,133 @@
+/// A splice that is applied sequentially to a `Vec` of elements.
+///
+/// When the splice is created, the current elements are moved
+/// (in reverse order) into a scratch buffer.
+/// Each operation then either pops the next element from the scratch buffer
+/// or pushes a (new or retained) element to the end of the `Vec`.
+/// Once the splice is dropped, all elements that haven't been visited
+/// are moved back, so a complete rebuild is `O(n)`.
+///
+/// The scratch buffer is owned by the caller so that
+/// its allocation can be reused between rebuilds.
+pub struct VecSplice<'v, 's, T> {
+    elements: &'v mut Vec<T>,
+    scratch: &'s mut Vec<T>,
+}
+
+impl<'v, 's, T> VecSplice<'v, 's, T> {
+    #[must_use]
+    pub fn new(elements: &'v mut Vec<T>, scratch: &'s mut Vec<T>) -> Self {
+        debug_assert!(scratch.is_empty());
+        scratch.extend(elements.drain(..).rev());
+        Self { elements, scratch }
+    }
+
+    /// Insert a new element at the current position.
+    pub fn insert(&mut self, element: T) {
+        self.elements.push(element);
+    }
+
+    /// Insert multiple new elements at the current position.
+    pub fn extend(&mut self, elements: impl IntoIterator<Item = T>) {
+        self.elements.extend(elements);
+    }
+
+    /// Retain the next element and return it for mutation.
+    ///
+    /// # Panics
+    ///
+    /// If there are no elements left.
+    pub fn mutate(&mut self) -> &mut T {
+        let element = self.scratch.pop().expect("No element left to mutate");
+        self.elements.push(element);
+        self.elements
+            .last_mut()
+            .expect("Element has just been pushed")
+    }
+
+    /// Retain the next `n` elements unchanged.
+    pub fn skip(&mut self, n: usize) {
+        debug_assert!(n <= self.scratch.len());
+        let n = n.min(self.scratch.len());
+        let start = self.scratch.len() - n;
+        self.elements.extend(self.scratch.drain(start..).rev());
+    }
+
+    /// Remove the next element and return it.
+    ///
+    /// # Panics
+    ///
+    /// If there are no elements left.
+    pub fn delete(&mut self) -> T {
+        self.scratch.pop().expect("No element left to delete")
+    }
+}
+
+impl<T> Drop for VecSplice<'_, '_, T> {
+    fn drop(&mut self) {
+        self.elements.extend(self.scratch.drain(..).rev());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VecSplice;
+
+    #[test]
+    fn apply_operations_in_order() {
+        let mut elements = vec![1, 2, 3, 4, 5];
+        let mut scratch = Vec::new();
+        {
+            let mut splice = VecSplice::new(&mut elements, &mut scratch);
+            splice.insert(0);
+            *splice.mutate() *= 10;
+            assert_eq!(splice.delete(), 2);
+            splice.skip(2);
+            splice.extend([6, 7]);
+            assert_eq!(splice.delete(), 5);
+            splice.insert(8);
+        }
+        assert_eq!(elements, vec![0, 10, 3, 4, 6, 7, 8]);
+        assert!(scratch.is_empty());
+    }
+
+    #[test]
+    fn restore_unvisited_elements_on_drop() {
+        let mut elements = vec![1, 2, 3, 4];
+        let mut scratch = Vec::new();
+        {
+            let mut splice = VecSplice::new(&mut elements, &mut scratch);
+            splice.skip(1);
+            splice.insert(5);
+        }
+        assert_eq!(elements, vec![1, 5, 2, 3, 4]);
+        assert!(scratch.is_empty());
+
+        // The scratch buffer can be reused.
+        drop(VecSplice::new(&mut elements, &mut scratch));
+        assert_eq!(elements, vec![1, 5, 2, 3, 4]);
+        assert!(scratch.is_empty());
+    }
+
+    #[test]
+    fn skip_everything() {
+        let mut elements = vec![1, 2, 3];
+        let mut scratch = Vec::new();
+        {
+            let mut splice = VecSplice::new(&mut elements, &mut scratch);
+            splice.skip(3);
+            splice.insert(4);
+        }
+        assert_eq!(elements, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "No element left to delete")]
+    fn delete_without_elements() {
+        let mut elements = vec![1];
+        let mut scratch = Vec::new();
+        let mut splice = VecSplice::new(&mut elements, &mut scratch);
+        splice.delete();
+        splice.delete();
+    }
+}
@@ -3,7 +3,7 @@ use xilem_web::{
     DynMessage,
 };
 
-use crate::{MapChildElement, MapCtx};
+use crate::{MapBackend, MapChildElement, MapCtx};
 
 pub const fn tile_layer(url_template: &'static str) -> TileLayer {
     TileLayer { url_template }
@@ -15,17 +15,16 @@ pub struct TileLayer {
 
 impl ViewMarker for TileLayer {}
 
-impl<State, Action> View<State, Action, MapCtx, DynMessage> for TileLayer
+impl<State, Action, B: MapBackend> View<State, Action, MapCtx<B>, DynMessage> for TileLayer
 where
     State: 'static,
 {
-    type Element = MapChildElement;
+    type Element = MapChildElement<B>;
 
     type ViewState = ();
 
-    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
-        let tile_layer = leaflet::TileLayer::new(self.url_template);
-        ctx.map().add_layer(&tile_layer);
+    fn build(&self, ctx: &mut MapCtx<B>) -> (Self::Element, Self::ViewState) {
+        let tile_layer = ctx.backend_mut().add_tile_layer(self.url_template);
         (MapChildElement::TileLayer(tile_layer), ())
     }
 
@@ -33,19 +32,27 @@ where
         &self,
         prev: &Self,
         _: &mut Self::ViewState,
-        map_ctx: &mut MapCtx,
+        map_ctx: &mut MapCtx<B>,
         element: Mut<Self::Element>,
     ) {
-        if prev.url_template != self.url_template {
-            let tile_layer = element.as_tile_layer_mut();
-            tile_layer.remove();
-            *tile_layer = leaflet::TileLayer::new(self.url_template);
-            tile_layer.add_to(map_ctx.map());
+        if prev.url_template == self.url_template {
+            return;
         }
+        let Some(tile_layer) = element.as_tile_layer_mut() else {
+            log::error!("The element of a tile layer is not a tile layer: {element:?}");
+            return;
+        };
+        let backend = map_ctx.backend_mut();
+        backend.remove_tile_layer(tile_layer);
+        *tile_layer = backend.add_tile_layer(self.url_template);
     }
 
-    fn teardown(&self, _: &mut Self::ViewState, _: &mut MapCtx, e: Mut<Self::Element>) {
-        e.as_tile_layer_mut().remove();
+    fn teardown(&self, _: &mut Self::ViewState, ctx: &mut MapCtx<B>, e: Mut<Self::Element>) {
+        let Some(tile_layer) = e.as_tile_layer_mut() else {
+            log::error!("The element of a tile layer is not a tile layer: {e:?}");
+            return;
+        };
+        ctx.backend_mut().remove_tile_layer(tile_layer);
     }
 
     fn message(
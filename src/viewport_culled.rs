@@ -0,0 +1,207 @@
+use std::{hash::Hash, marker::PhantomData, rc::Rc};
+
+use xilem_web::{
+    core::{MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker as _},
+    DynMessage,
+};
+
+use crate::{
+    keyed::{keyed, KeyedViewState},
+    map::{is_loaded, EventListener},
+    visible_items, Keyed, LatLngBounds, MapChildElement, MapCtx, Spatial, SpatialIndex,
+};
+
+/// Distinctive ID for better debugging
+const VIEWPORT_CULLED_ID: ViewId = ViewId::new(23676);
+
+/// The children are wrapped with their own ID
+/// to distinguish their messages from the ones of the culling view.
+const VIEWPORT_CULLED_CHILDREN_ID: ViewId = ViewId::new(23677);
+
+/// Default margin around the visible bounds (see [`ViewportCulled::margin`]).
+const DEFAULT_MARGIN: f64 = 0.5;
+
+/// Only create the children for the items within the visible bounds of the map.
+///
+/// The items are stored in an R-tree (see [`SpatialIndex`]), which is only
+/// recreated when `items` is not the same `Rc` as in the previous view.
+/// Whenever the map has been moved or zoomed, the children of the items
+/// within the visible bounds (plus a margin) are created and the
+/// others are removed, diffed by their key as with [`keyed`].
+/// No children are created until the view of the map has been set.
+pub fn viewport_culled<State, T, K, V, FK, FV>(
+    items: Rc<[T]>,
+    key: FK,
+    view: FV,
+) -> ViewportCulled<State, T, FK, FV>
+where
+    T: Spatial,
+    K: Hash + Eq,
+    FK: Fn(&T) -> K,
+    FV: Fn(&T) -> V,
+{
+    ViewportCulled {
+        items,
+        key,
+        view,
+        margin: DEFAULT_MARGIN,
+        phantom: PhantomData,
+    }
+}
+
+pub struct ViewportCulled<State, T, FK, FV> {
+    items: Rc<[T]>,
+    key: FK,
+    view: FV,
+    margin: f64,
+    phantom: PhantomData<fn() -> State>,
+}
+
+impl<State, T, FK, FV> ViewportCulled<State, T, FK, FV> {
+    /// Extend the visible bounds by this part of their size on each side
+    /// (see [`pad_bounds`](crate::pad_bounds)), so that small movements don't create new children.
+    #[must_use]
+    pub const fn margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// The children for the items within the bounds (`None` if the view of the map has not been set).
+    fn visible_children<K, V>(
+        &self,
+        index: &SpatialIndex,
+        bounds: Option<&LatLngBounds>,
+    ) -> Keyed<K, V>
+    where
+        K: Hash + Eq,
+        FK: Fn(&T) -> K,
+        FV: Fn(&T) -> V,
+    {
+        let indices = bounds
+            .map(|bounds| visible_items(index, bounds, self.margin))
+            .unwrap_or_default();
+        keyed(
+            indices.into_iter().map(|idx| &self.items[idx]),
+            |item| (self.key)(*item),
+            |item| (self.view)(item),
+        )
+    }
+}
+
+/// The visible bounds of the map (`None` until its view has been set).
+fn map_bounds(map: &leaflet::Map) -> Option<LatLngBounds> {
+    is_loaded(map).then(|| map.get_bounds().into())
+}
+
+impl<State, T, FK, FV> ViewMarker for ViewportCulled<State, T, FK, FV> {}
+
+#[derive(Debug)]
+struct ViewportChanged;
+
+pub struct ViewportCulledViewState<K, V, S> {
+    index: SpatialIndex,
+    /// The most recent children, to be diffed with the next ones.
+    children: Keyed<K, V>,
+    children_state: KeyedViewState<K, S>,
+    _on_move_end: EventListener<leaflet::Map>,
+}
+
+impl<State, Action, T, K, V, FK, FV> View<State, Action, MapCtx, DynMessage>
+    for ViewportCulled<State, T, FK, FV>
+where
+    State: 'static,
+    Action: 'static,
+    T: Spatial + 'static,
+    K: Hash + Eq + Clone + 'static,
+    V: View<State, Action, MapCtx, DynMessage, Element = MapChildElement>,
+    FK: Fn(&T) -> K + 'static,
+    FV: Fn(&T) -> V + 'static,
+{
+    type Element = MapChildElement;
+
+    type ViewState = ViewportCulledViewState<K, V, V::ViewState>;
+
+    fn build(&self, ctx: &mut MapCtx) -> (Self::Element, Self::ViewState) {
+        let index = SpatialIndex::new(self.items.iter());
+        let children = self.visible_children(&index, map_bounds(ctx.map()).as_ref());
+        let (element, children_state) = ctx.with_id(VIEWPORT_CULLED_CHILDREN_ID, |ctx| {
+            View::<State, Action, MapCtx, _>::build(&children, ctx)
+        });
+        let on_move_end = ctx.with_id(VIEWPORT_CULLED_ID, |ctx| {
+            let thunk = ctx.message_thunk();
+            EventListener::new(ctx.map(), "moveend", move |_: leaflet::Event| {
+                thunk.push_message(ViewportChanged);
+            })
+        });
+        let view_state = ViewportCulledViewState {
+            index,
+            children,
+            children_state,
+            _on_move_end: on_move_end,
+        };
+        (element, view_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx,
+        element: Mut<Self::Element>,
+    ) {
+        if !Rc::ptr_eq(&prev.items, &self.items) {
+            view_state.index = SpatialIndex::new(self.items.iter());
+        }
+        let bounds = map_bounds(ctx.map());
+        let children = self.visible_children(&view_state.index, bounds.as_ref());
+        ctx.with_id(VIEWPORT_CULLED_CHILDREN_ID, |ctx| {
+            View::<State, Action, MapCtx, _>::rebuild(
+                &children,
+                &view_state.children,
+                &mut view_state.children_state,
+                ctx,
+                element,
+            );
+        });
+        view_state.children = children;
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut MapCtx,
+        element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(VIEWPORT_CULLED_CHILDREN_ID, |ctx| {
+            View::<State, Action, MapCtx, _>::teardown(
+                &view_state.children,
+                &mut view_state.children_state,
+                ctx,
+                element,
+            );
+        });
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        state: &mut State,
+    ) -> MessageResult<Action, DynMessage> {
+        match id_path.split_first() {
+            Some((first, rest)) if *first == VIEWPORT_CULLED_CHILDREN_ID => {
+                View::<State, Action, MapCtx, _>::message(
+                    &view_state.children,
+                    &mut view_state.children_state,
+                    rest,
+                    message,
+                    state,
+                )
+            }
+            // The children are updated by the rebuild.
+            Some((first, [])) if *first == VIEWPORT_CULLED_ID => MessageResult::RequestRebuild,
+            _ => MessageResult::Stale(message),
+        }
+    }
+}
@@ -0,0 +1,447 @@
+//! Run with `wasm-pack test --headless --firefox` (or `--chrome`).
+#![cfg(target_arch = "wasm32")]
+
+mod harness;
+
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+use web_sys::wasm_bindgen::{JsCast as _, UnwrapThrowExt as _};
+use xilem_leaflet::{
+    custom_layer, keyed, leaflet_assets, map, map_container, marker, tile_layer, AnyMapView, Crs,
+    CrsError, Either, InvalidLatLng, LatLng, MapError, MapHandle, MapLayer, Point, Proj4Crs,
+};
+use xilem_web::{elements::html, interfaces::Element as _, DomView};
+
+use self::harness::{load_leaflet, next_event, Harness};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// A transparent image of 1x1 pixels as tile URL (to avoid network requests).
+const TILE_URL: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=";
+
+const CENTER: LatLng = LatLng::new(48.64, 9.46);
+
+struct MapState {
+    markers: Vec<(u32, f64, f64)>,
+    zoom: f64,
+    zoom_end_count: usize,
+    clicks: Vec<(f64, f64)>,
+    leaflet_map: Option<leaflet::Map>,
+}
+
+impl MapState {
+    fn new(markers: Vec<(u32, f64, f64)>) -> Self {
+        Self {
+            markers,
+            zoom: 12.0,
+            zoom_end_count: 0,
+            clicks: Vec::new(),
+            leaflet_map: None,
+        }
+    }
+}
+
+fn app_logic(state: &mut MapState) -> impl DomView<MapState> {
+    let markers = keyed(
+        state.markers.iter().copied(),
+        |(key, _, _)| *key,
+        |(_, lat, lng)| marker(LatLng::new(lat, lng)),
+    );
+    map((tile_layer(TILE_URL), markers))
+        .center(CENTER)
+        .zoom(state.zoom)
+        .on_zoom_end(|state: &mut MapState, map, _| {
+            state.zoom = map.get_zoom();
+            state.zoom_end_count += 1;
+        })
+        .on_move_end(|state: &mut MapState, map, _| {
+            state.leaflet_map = Some(map);
+        })
+        .on_mouse_click(|state: &mut MapState, ev| {
+            let lat_lng = ev.lat_lng();
+            state.clicks.push((lat_lng.lat(), lat_lng.lng()));
+        })
+}
+
+async fn mount(markers: Vec<(u32, f64, f64)>) -> Harness<MapState> {
+    load_leaflet().await;
+    let harness = Harness::mount(MapState::new(markers), app_logic);
+    // The view is set once the container has been attached.
+    harness.until(|state| state.leaflet_map.is_some()).await;
+    harness
+}
+
+#[wasm_bindgen_test]
+async fn render_tile_layer_and_markers() {
+    let harness = mount(vec![(1, 48.64, 9.46), (2, 48.65, 9.47)]).await;
+
+    assert_eq!(harness.query_all(".leaflet-marker-icon").len(), 2);
+    let tiles = harness.query_all("img.leaflet-tile");
+    assert!(!tiles.is_empty());
+    for tile in tiles {
+        let tile: web_sys::HtmlImageElement = tile.unchecked_into();
+        assert_eq!(tile.src(), TILE_URL);
+    }
+
+    let leaflet_map = harness
+        .read(|state| state.leaflet_map.clone())
+        .expect("the map has not been moved");
+    assert!((leaflet_map.get_zoom() - 12.0).abs() < f64::EPSILON);
+    let center = leaflet_map.get_center();
+    assert!((center.lat() - CENTER.lat).abs() < 1e-6);
+    assert!((center.lng() - CENTER.lng).abs() < 1e-6);
+}
+
+#[wasm_bindgen_test]
+async fn load_the_script_and_the_stylesheet_once() {
+    load_leaflet().await;
+    assert!(leaflet_assets::is_script_loaded());
+    assert!(leaflet_assets::is_stylesheet_loaded());
+
+    let head = xilem_web::document().head().unwrap_throw();
+    let element_count = head.child_element_count();
+    load_leaflet().await;
+    assert_eq!(head.child_element_count(), element_count);
+}
+
+#[wasm_bindgen_test]
+async fn rebuild_keeps_markers_of_unchanged_keys() {
+    let harness = mount(vec![(1, 48.64, 9.46), (2, 48.65, 9.47)]).await;
+    let icons = harness.query_all(".leaflet-marker-icon");
+
+    harness.update(|state| {
+        state.markers = vec![(2, 48.66, 9.48), (3, 48.63, 9.45)];
+    });
+
+    assert_eq!(harness.query_all(".leaflet-marker-icon").len(), 2);
+    assert!(
+        !icons[0].is_connected(),
+        "removed marker is still on the map"
+    );
+    assert!(icons[1].is_connected(), "moved marker has been recreated");
+}
+
+#[wasm_bindgen_test]
+#[allow(clippy::cast_possible_truncation)]
+async fn zoom_end_and_mouse_click_events() {
+    let harness = mount(Vec::new()).await;
+
+    harness.update(|state| state.zoom = 13.0);
+    // Wait for the zoom animation.
+    harness.until(|state| state.zoom_end_count > 0).await;
+    let (zoom, zoom_end_count) = harness.read(|state| (state.zoom, state.zoom_end_count));
+    assert!((zoom - 13.0).abs() < f64::EPSILON);
+    assert_eq!(zoom_end_count, 1);
+
+    let container = harness
+        .query_all(".leaflet-container")
+        .pop()
+        .expect("no map container");
+    let rect = container.get_bounding_client_rect();
+    let init = web_sys::MouseEventInit::new();
+    init.set_bubbles(true);
+    init.set_client_x((rect.left() + rect.width() / 2.0) as i32);
+    init.set_client_y((rect.top() + rect.height() / 2.0) as i32);
+    let click = web_sys::MouseEvent::new_with_mouse_event_init_dict("click", &init).unwrap_throw();
+    container.dispatch_event(&click).unwrap_throw();
+
+    let clicks = harness.read(|state| state.clicks.clone());
+    assert_eq!(clicks.len(), 1);
+    let (lat, lng) = clicks[0];
+    assert!((lat - CENTER.lat).abs() < 0.01);
+    assert!((lng - CENTER.lng).abs() < 0.01);
+    // Each event is only handled by its own view.
+    assert_eq!(harness.read(|state| state.zoom_end_count), 1);
+}
+
+#[derive(Default)]
+struct ResizeState {
+    ready: bool,
+    size: Option<Point>,
+}
+
+#[wasm_bindgen_test]
+async fn resize_with_the_container() {
+    load_leaflet().await;
+    let harness = Harness::mount(ResizeState::default(), |_: &mut ResizeState| {
+        map(tile_layer(TILE_URL))
+            .center(CENTER)
+            .zoom(12.0)
+            .on_ready(|state: &mut ResizeState, _| state.ready = true)
+            .on_resize(|state: &mut ResizeState, new_size| state.size = Some(new_size))
+    });
+    harness.until(|state| state.ready).await;
+
+    harness.resize(200, 300);
+    harness.until(|state| state.size.is_some()).await;
+    assert_eq!(
+        harness.read(|state| state.size),
+        Some(Point::new(200.0, 300.0))
+    );
+}
+
+struct ViewState {
+    zoom: f64,
+    center: LatLng,
+    handle: Option<MapHandle>,
+}
+
+#[wasm_bindgen_test]
+async fn change_the_view_before_the_container_is_attached() {
+    load_leaflet().await;
+    let state = ViewState {
+        zoom: 12.0,
+        center: CENTER,
+        handle: None,
+    };
+    let harness = Harness::mount(state, |state: &mut ViewState| {
+        map(tile_layer(TILE_URL))
+            .center(state.center)
+            .zoom(state.zoom)
+            .on_ready(|state: &mut ViewState, handle| state.handle = Some(handle))
+    });
+    // Before the size of the container has been observed.
+    harness.detach();
+    harness.update(|state| state.zoom = 10.0);
+    harness.update(|state| state.center = LatLng::new(CENTER.lat + 1.0, CENTER.lng - 1.0));
+    assert!(harness.read(|state| state.handle.is_none()));
+
+    harness.attach();
+    harness.until(|state| state.handle.is_some()).await;
+    let handle = harness
+        .read(|state| state.handle.clone())
+        .expect("map is not ready");
+    assert_eq!(handle.map().get_zoom(), 10.0);
+    let center = handle.map().get_center();
+    assert!((center.lat() - (CENTER.lat + 1.0)).abs() < 1e-6);
+    assert!((center.lng() - (CENTER.lng - 1.0)).abs() < 1e-6);
+}
+
+#[wasm_bindgen_test]
+async fn custom_container_keeps_the_leaflet_classes() {
+    load_leaflet().await;
+    let harness = Harness::mount(false, |dark: &mut bool| {
+        let theme = if *dark { "dark" } else { "light" };
+        map(tile_layer(TILE_URL))
+            .center(CENTER)
+            .zoom(12.0)
+            .container(map_container().class(theme).attr("data-testid", "map"))
+    });
+    // Leaflet adds its classes when the map is created (synchronously).
+    assert_eq!(
+        harness
+            .query_all("[data-testid=map].light.leaflet-container")
+            .len(),
+        1
+    );
+
+    harness.update(|dark| *dark = true);
+    assert_eq!(
+        harness
+            .query_all("[data-testid=map].dark.leaflet-container")
+            .len(),
+        1
+    );
+    assert!(harness.query_all(".light").is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn conditional_children() {
+    load_leaflet().await;
+    // The mode and whether the map is ready.
+    let harness = Harness::mount((0_u8, false), |(mode, _): &mut (u8, bool)| {
+        let extra_marker = (*mode == 1).then(|| marker(CENTER));
+        let layer = if *mode == 2 {
+            Either::Left(tile_layer(TILE_URL))
+        } else {
+            Either::Right(marker(LatLng::new(CENTER.lat + 0.01, CENTER.lng)))
+        };
+        let boxed: Box<AnyMapView<(u8, bool)>> = if *mode == 0 {
+            Box::new(marker(LatLng::new(CENTER.lat - 0.01, CENTER.lng)))
+        } else {
+            Box::new(tile_layer(TILE_URL))
+        };
+        map((extra_marker, layer, boxed))
+            .center(CENTER)
+            .zoom(12.0)
+            .on_ready(|(_, ready): &mut (u8, bool), _| *ready = true)
+    });
+    // Leaflet renders the layers once the view has been set.
+    harness.until(|(_, ready)| *ready).await;
+    assert_eq!(harness.query_all(".leaflet-marker-icon").len(), 2);
+    assert!(harness.query_all("img.leaflet-tile").is_empty());
+
+    harness.update(|(mode, _)| *mode = 1);
+    assert_eq!(harness.query_all(".leaflet-marker-icon").len(), 2);
+    assert_eq!(harness.query_all(".leaflet-layer").len(), 1);
+
+    harness.update(|(mode, _)| *mode = 2);
+    assert!(harness.query_all(".leaflet-marker-icon").is_empty());
+    assert_eq!(harness.query_all(".leaflet-layer").len(), 2);
+}
+
+#[wasm_bindgen_test]
+async fn map_handle_once_ready() {
+    load_leaflet().await;
+    let harness = Harness::mount(None, |_: &mut Option<MapHandle>| {
+        map(tile_layer(TILE_URL))
+            .center(CENTER)
+            .zoom(12.0)
+            .on_ready(|handle: &mut Option<MapHandle>, ready| *handle = Some(ready))
+    });
+    harness.until(Option::is_some).await;
+    let handle = harness
+        .read(|handle| handle.clone())
+        .expect("map is not ready");
+    assert!(handle
+        .container()
+        .class_list()
+        .contains("leaflet-container"));
+    assert_eq!(handle.map().get_zoom(), 12.0);
+
+    // The zoom might be animated.
+    let zoom_end = next_event(handle.map(), "zoomend");
+    handle.set_view(CENTER, 10.0);
+    zoom_end.await.unwrap_throw();
+    assert_eq!(handle.map().get_zoom(), 10.0);
+}
+
+/// A marker as a stand-in for the layer of a plugin.
+struct PluginLayer {
+    lat: f64,
+    lng: f64,
+}
+
+impl MapLayer for PluginLayer {
+    type Layer = leaflet::Marker;
+
+    fn create(&self, _: &leaflet::Map) -> leaflet::Marker {
+        leaflet::Marker::new(&leaflet::LatLng::new(self.lat, self.lng))
+    }
+
+    fn update(&self, prev: &Self, layer: &leaflet::Marker) {
+        if (self.lat, self.lng) != (prev.lat, prev.lng) {
+            layer.set_lat_lng(&leaflet::LatLng::new(self.lat, self.lng));
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+async fn add_update_and_remove_a_custom_layer() {
+    load_leaflet().await;
+    // The position of the layer and whether the map is ready.
+    type LayerState = (Option<LatLng>, bool);
+    let harness = Harness::mount((Some(CENTER), false), |(position, _): &mut LayerState| {
+        let layer = position.map(|LatLng { lat, lng }| custom_layer(PluginLayer { lat, lng }));
+        map(layer)
+            .center(CENTER)
+            .zoom(12.0)
+            .on_ready(|(_, ready): &mut LayerState, _| *ready = true)
+    });
+    harness.until(|(_, ready)| *ready).await;
+    let icons = harness.query_all(".leaflet-marker-icon");
+    assert_eq!(icons.len(), 1);
+
+    harness.update(|(position, _)| *position = Some(LatLng::new(CENTER.lat + 0.01, CENTER.lng)));
+    assert!(icons[0].is_connected());
+
+    harness.update(|(position, _)| *position = None);
+    assert!(harness.query_all(".leaflet-marker-icon").is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn report_a_crs_that_can_not_be_created() {
+    load_leaflet().await;
+    let harness = Harness::mount(Vec::new(), |_: &mut Vec<MapError>| {
+        let crs = Proj4Crs::new(
+            "EPSG:3006",
+            "+proj=utm +zone=33 +ellps=GRS80 +units=m +no_defs",
+        );
+        map(tile_layer(TILE_URL))
+            .center(CENTER)
+            .zoom(12.0)
+            .crs(Crs::Proj4(crs))
+            .on_error(|errors: &mut Vec<MapError>, error| errors.push(error))
+    });
+    harness.until(|errors| !errors.is_empty()).await;
+    let errors = harness.read(Clone::clone);
+    assert_eq!(errors, [MapError::Crs(CrsError::Proj4Missing)]);
+    // The map is displayed with the default CRS.
+    assert_eq!(harness.query_all(".leaflet-layer").len(), 1);
+}
+
+#[derive(Default)]
+struct SimpleCrsState {
+    handle: Option<MapHandle>,
+    errors: Vec<MapError>,
+}
+
+#[wasm_bindgen_test]
+async fn marker_beyond_the_latitude_range_of_a_simple_crs() {
+    // E.g. a pixel of a floor plan.
+    const POSITION: LatLng = LatLng::new(500.0, 800.0);
+
+    load_leaflet().await;
+    let harness = Harness::mount(SimpleCrsState::default(), |_: &mut SimpleCrsState| {
+        map(marker(POSITION))
+            .center(POSITION)
+            .zoom(0.0)
+            .crs(Crs::Simple)
+            .on_ready(|state: &mut SimpleCrsState, handle| state.handle = Some(handle))
+            .on_error(|state: &mut SimpleCrsState, error| state.errors.push(error))
+    });
+    harness.until(|state| state.handle.is_some()).await;
+    assert!(harness.read(|state| state.errors.is_empty()));
+    assert_eq!(harness.query_all(".leaflet-marker-icon").len(), 1);
+    let center = harness
+        .read(|state| {
+            state
+                .handle
+                .as_ref()
+                .map(|handle| handle.map().get_center())
+        })
+        .expect("map is not ready");
+    assert!((center.lat() - POSITION.lat).abs() < 1e-6);
+    assert!((center.lng() - POSITION.lng).abs() < 1e-6);
+}
+
+#[wasm_bindgen_test]
+async fn report_an_invalid_center_of_a_geographic_crs() {
+    load_leaflet().await;
+    let harness = Harness::mount(Vec::new(), |_: &mut Vec<MapError>| {
+        map(tile_layer(TILE_URL))
+            .center(LatLng::new(500.0, 800.0))
+            .zoom(12.0)
+            .on_error(|errors: &mut Vec<MapError>, error| errors.push(error))
+    });
+    harness.until(|errors| !errors.is_empty()).await;
+    let errors = harness.read(Clone::clone);
+    assert_eq!(
+        errors,
+        [MapError::InvalidCenter(InvalidLatLng::LatitudeOutOfRange)]
+    );
+}
+
+#[cfg(debug_assertions)]
+#[wasm_bindgen_test]
+async fn teardown_releases_the_map() {
+    use xilem_leaflet::leak_check::{live_listeners, live_maps};
+
+    load_leaflet().await;
+    let (maps, listeners) = (live_maps(), live_listeners());
+    let harness = Harness::mount(true, |show: &mut bool| {
+        html::div(show.then(|| {
+            map(marker(CENTER))
+                .center(CENTER)
+                .zoom(12.0)
+                .on_move_end(|_: &mut bool, _, _| {})
+        }))
+    });
+    assert_eq!(live_maps(), maps + 1);
+    // The `moveend` listener and the observer of the container size.
+    assert_eq!(live_listeners(), listeners + 2);
+
+    harness.update(|show| *show = false);
+    assert_eq!(live_maps(), maps);
+    assert_eq!(live_listeners(), listeners);
+    assert!(harness.query_all(".leaflet-container").is_empty());
+}
@@ -0,0 +1,181 @@
+//! Mount views in the browser and drive their rebuilds.
+
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    js_sys::{Function, Promise, Reflect},
+    wasm_bindgen::{JsCast, JsValue, UnwrapThrowExt as _},
+};
+use xilem_leaflet::leaflet_assets;
+use xilem_web::{
+    document, elements::html, interfaces::Element as _, modifiers::style, App, DomView,
+};
+
+/// The custom DOM event that applies a pending update to the state.
+const UPDATE_EVENT: &str = "test-harness-update";
+
+/// Load the Leaflet script and stylesheet (once).
+pub async fn load_leaflet() {
+    leaflet_assets::load()
+        .await
+        .expect("Leaflet could not be loaded");
+}
+
+/// The next event of the type that a Leaflet object (e.g. the map) fires.
+///
+/// The listener is added right away,
+/// so the future can be created before the call that fires the event.
+pub fn next_event(target: &JsValue, event: &str) -> JsFuture {
+    let once: Function = Reflect::get(target, &"once".into())
+        .unwrap_throw()
+        .unchecked_into();
+    let promise = Promise::new(&mut |resolve, _| {
+        once.call2(target, &event.into(), &resolve).unwrap_throw();
+    });
+    JsFuture::from(promise)
+}
+
+type Update<State> = Box<dyn FnOnce(&mut State)>;
+
+/// A condition on the state that is awaited with [`Harness::until`].
+struct Waiting<State> {
+    condition: Box<dyn Fn(&State) -> bool>,
+    resolve: Function,
+}
+
+/// A running [`App`] within a container of a fixed size.
+pub struct Harness<State> {
+    container: web_sys::HtmlElement,
+    pending_update: Rc<RefCell<Option<Update<State>>>>,
+    waiting: Rc<RefCell<Option<Waiting<State>>>>,
+}
+
+impl<State: 'static> Harness<State> {
+    pub fn mount<V>(state: State, logic: impl Fn(&mut State) -> V + 'static) -> Self
+    where
+        V: DomView<State> + 'static,
+    {
+        let document = document();
+        let container: web_sys::HtmlElement = document
+            .create_element("div")
+            .unwrap_throw()
+            .unchecked_into();
+        set_size(&container, 400, 300);
+        document
+            .body()
+            .unwrap_throw()
+            .append_child(&container)
+            .unwrap_throw();
+        let pending_update = Rc::new(RefCell::new(None::<Update<State>>));
+        let waiting = Rc::new(RefCell::new(None::<Waiting<State>>));
+        let app_logic = {
+            let pending_update = Rc::clone(&pending_update);
+            let waiting = Rc::clone(&waiting);
+            move |state: &mut State| {
+                // The app logic is called after every change of the state.
+                let fulfilled = waiting
+                    .borrow_mut()
+                    .take_if(|waiting| (waiting.condition)(&*state));
+                if let Some(waiting) = fulfilled {
+                    waiting.resolve.call0(&JsValue::UNDEFINED).unwrap_throw();
+                }
+                let pending_update = Rc::clone(&pending_update);
+                html::div(logic(state))
+                    .style([style("width", "100%"), style("height", "100%")])
+                    .on(
+                        UPDATE_EVENT,
+                        move |state: &mut State, _: web_sys::CustomEvent| {
+                            let update = pending_update.borrow_mut().take();
+                            if let Some(update) = update {
+                                update(state);
+                            }
+                        },
+                    )
+            }
+        };
+        App::new(container.clone(), state, app_logic).run();
+        Self {
+            container,
+            pending_update,
+            waiting,
+        }
+    }
+
+    /// Remove the container from the document (the app keeps running).
+    pub fn detach(&self) {
+        self.container.remove();
+    }
+
+    /// Append the container to the document again.
+    pub fn attach(&self) {
+        document()
+            .body()
+            .unwrap_throw()
+            .append_child(&self.container)
+            .unwrap_throw();
+    }
+
+    /// Change the size of the container (in pixels).
+    pub fn resize(&self, width: u32, height: u32) {
+        set_size(&self.container, width, height);
+    }
+
+    /// Change the state and rebuild the views synchronously.
+    pub fn update(&self, update: impl FnOnce(&mut State) + 'static) {
+        *self.pending_update.borrow_mut() = Some(Box::new(update));
+        let root = self
+            .container
+            .first_element_child()
+            .expect("app has not been mounted");
+        let event = web_sys::CustomEvent::new(UPDATE_EVENT).unwrap_throw();
+        root.dispatch_event(&event).unwrap_throw();
+    }
+
+    /// Wait until the state fulfills the condition,
+    /// e.g. after an event of the map has been handled.
+    pub async fn until(&self, condition: impl Fn(&State) -> bool + 'static) {
+        let mut resolve = None;
+        let promise = Promise::new(&mut |resolve_promise, _| resolve = Some(resolve_promise));
+        *self.waiting.borrow_mut() = Some(Waiting {
+            condition: Box::new(condition),
+            resolve: resolve.unwrap_throw(),
+        });
+        // The condition might already be fulfilled.
+        self.update(|_| {});
+        JsFuture::from(promise).await.unwrap_throw();
+    }
+
+    pub fn read<R: 'static>(&self, read: impl FnOnce(&State) -> R + 'static) -> R {
+        let result = Rc::new(RefCell::new(None));
+        {
+            let result = Rc::clone(&result);
+            self.update(move |state| *result.borrow_mut() = Some(read(state)));
+        }
+        result.take().expect("state has not been read")
+    }
+
+    pub fn query_all(&self, selector: &str) -> Vec<web_sys::Element> {
+        let nodes = self.container.query_selector_all(selector).unwrap_throw();
+        (0..nodes.length())
+            .filter_map(|idx| nodes.item(idx))
+            .map(JsCast::unchecked_into)
+            .collect()
+    }
+}
+
+fn set_size(element: &web_sys::HtmlElement, width: u32, height: u32) {
+    let element_style = element.style();
+    element_style
+        .set_property("width", &format!("{width}px"))
+        .unwrap_throw();
+    element_style
+        .set_property("height", &format!("{height}px"))
+        .unwrap_throw();
+}
+
+impl<State> Drop for Harness<State> {
+    fn drop(&mut self) {
+        self.container.remove();
+    }
+}
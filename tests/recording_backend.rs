@@ -0,0 +1,231 @@
+use xilem_leaflet::{
+    keyed, marker, tile_layer, AnyMapView, BackendOp, Either, Keyed, LatLng, LayerId, MapCtx,
+    Marker, RecordingBackend, TileLayer,
+};
+use xilem_web::{core::View, DynMessage};
+
+fn markers(positions: &[(u32, f64, f64)]) -> Keyed<u32, Marker> {
+    keyed(
+        positions.iter().copied(),
+        |(key, _, _)| *key,
+        |(_, lat, lng)| marker(LatLng::new(lat, lng)),
+    )
+}
+
+#[test]
+fn rebuild_with_one_moved_marker() {
+    let mut ctx = MapCtx::with_backend(RecordingBackend::new());
+    let prev = markers(&[(1, 0.0, 0.0), (2, 1.0, 1.0)]);
+    let (mut element, mut state) = View::<(), (), _, DynMessage>::build(&prev, &mut ctx);
+    assert_eq!(ctx.backend_mut().take_ops().len(), 2);
+
+    let next = markers(&[(1, 0.0, 0.0), (2, 2.0, 2.0)]);
+    View::<(), (), _, DynMessage>::rebuild(&next, &prev, &mut state, &mut ctx, &mut element);
+    assert_eq!(
+        ctx.backend_mut().take_ops(),
+        vec![BackendOp::SetMarkerPosition {
+            id: LayerId(1),
+            position: LatLng::new(2.0, 2.0)
+        }]
+    );
+}
+
+#[test]
+fn remove_markers() {
+    let mut ctx = MapCtx::with_backend(RecordingBackend::new());
+    let prev = markers(&[(1, 0.0, 0.0), (2, 1.0, 1.0)]);
+    let (mut element, mut state) = View::<(), (), _, DynMessage>::build(&prev, &mut ctx);
+    ctx.backend_mut().take_ops();
+
+    let next = markers(&[(2, 1.0, 1.0)]);
+    View::<(), (), _, DynMessage>::rebuild(&next, &prev, &mut state, &mut ctx, &mut element);
+    assert_eq!(
+        ctx.backend_mut().take_ops(),
+        vec![BackendOp::RemoveLayer(LayerId(0))]
+    );
+
+    View::<(), (), _, DynMessage>::teardown(&next, &mut state, &mut ctx, &mut element);
+    assert_eq!(
+        ctx.backend_mut().take_ops(),
+        vec![BackendOp::RemoveLayer(LayerId(1))]
+    );
+}
+
+const TILE_URL: &str = "https://tile.openstreetmap.org/{z}/{x}/{y}.png";
+
+#[test]
+fn switch_either_variant() {
+    let mut ctx = MapCtx::with_backend(RecordingBackend::new());
+    let prev: Either<Marker, TileLayer> = Either::Left(marker(LatLng::new(0.0, 0.0)));
+    let (mut element, mut state) = View::<(), (), _, DynMessage>::build(&prev, &mut ctx);
+    ctx.backend_mut().take_ops();
+
+    let next = Either::Right(tile_layer(TILE_URL));
+    View::<(), (), _, DynMessage>::rebuild(&next, &prev, &mut state, &mut ctx, &mut element);
+    assert_eq!(
+        ctx.backend_mut().take_ops(),
+        vec![
+            BackendOp::RemoveLayer(LayerId(0)),
+            BackendOp::AddTileLayer {
+                id: LayerId(1),
+                url_template: TILE_URL.to_string()
+            }
+        ]
+    );
+}
+
+#[test]
+fn replace_boxed_view_of_another_type() {
+    type BoxedView = Box<AnyMapView<(), (), RecordingBackend>>;
+
+    let mut ctx = MapCtx::with_backend(RecordingBackend::new());
+    let prev: BoxedView = Box::new(marker(LatLng::new(0.0, 0.0)));
+    let (mut element, mut state) = View::<(), (), _, DynMessage>::build(&prev, &mut ctx);
+    ctx.backend_mut().take_ops();
+
+    let next: BoxedView = Box::new(marker(LatLng::new(1.0, 1.0)));
+    View::<(), (), _, DynMessage>::rebuild(&next, &prev, &mut state, &mut ctx, &mut element);
+    assert_eq!(
+        ctx.backend_mut().take_ops(),
+        vec![BackendOp::SetMarkerPosition {
+            id: LayerId(0),
+            position: LatLng::new(1.0, 1.0)
+        }]
+    );
+
+    let prev = next;
+    let next: BoxedView = Box::new(tile_layer(TILE_URL));
+    View::<(), (), _, DynMessage>::rebuild(&next, &prev, &mut state, &mut ctx, &mut element);
+    assert_eq!(
+        ctx.backend_mut().take_ops(),
+        vec![
+            BackendOp::RemoveLayer(LayerId(0)),
+            BackendOp::AddTileLayer {
+                id: LayerId(1),
+                url_template: TILE_URL.to_string()
+            }
+        ]
+    );
+}
+
+#[test]
+fn insert_and_reorder_keyed_markers() {
+    let mut ctx = MapCtx::with_backend(RecordingBackend::new());
+    let prev = markers(&[(1, 0.0, 0.0), (2, 1.0, 1.0)]);
+    let (mut element, mut state) = View::<(), (), _, DynMessage>::build(&prev, &mut ctx);
+    ctx.backend_mut().take_ops();
+
+    // Reordering doesn't touch the layers of the existing keys.
+    let next = markers(&[(3, 3.0, 3.0), (2, 1.0, 1.0), (1, 0.0, 0.0)]);
+    View::<(), (), _, DynMessage>::rebuild(&next, &prev, &mut state, &mut ctx, &mut element);
+    assert_eq!(
+        ctx.backend_mut().take_ops(),
+        vec![BackendOp::AddMarker {
+            id: LayerId(2),
+            position: LatLng::new(3.0, 3.0)
+        }]
+    );
+
+    // The children are diffed by key, not by position.
+    let prev = next;
+    let next = markers(&[(1, 0.0, 0.0), (3, 3.0, 3.0), (2, 2.0, 2.0)]);
+    View::<(), (), _, DynMessage>::rebuild(&next, &prev, &mut state, &mut ctx, &mut element);
+    assert_eq!(
+        ctx.backend_mut().take_ops(),
+        vec![BackendOp::SetMarkerPosition {
+            id: LayerId(1),
+            position: LatLng::new(2.0, 2.0)
+        }]
+    );
+}
+
+#[test]
+fn keep_the_last_child_of_duplicate_keys() {
+    let mut ctx = MapCtx::with_backend(RecordingBackend::new());
+    let prev = markers(&[(1, 0.0, 0.0), (1, 1.0, 1.0)]);
+    let (mut element, mut state) = View::<(), (), _, DynMessage>::build(&prev, &mut ctx);
+    assert_eq!(
+        ctx.backend_mut().take_ops(),
+        vec![
+            BackendOp::AddMarker {
+                id: LayerId(0),
+                position: LatLng::new(0.0, 0.0)
+            },
+            BackendOp::AddMarker {
+                id: LayerId(1),
+                position: LatLng::new(1.0, 1.0)
+            },
+            BackendOp::RemoveLayer(LayerId(0)),
+        ]
+    );
+
+    let next = markers(&[(1, 1.0, 1.0)]);
+    View::<(), (), _, DynMessage>::rebuild(&next, &prev, &mut state, &mut ctx, &mut element);
+    assert!(ctx.backend_mut().take_ops().is_empty());
+
+    View::<(), (), _, DynMessage>::teardown(&next, &mut state, &mut ctx, &mut element);
+    assert_eq!(
+        ctx.backend_mut().take_ops(),
+        vec![BackendOp::RemoveLayer(LayerId(1))]
+    );
+}
+
+#[test]
+fn change_the_tile_layer_url() {
+    const OTHER_TILE_URL: &str = "https://tile.example.com/{z}/{x}/{y}.png";
+
+    let mut ctx = MapCtx::with_backend(RecordingBackend::new());
+    let prev = tile_layer(TILE_URL);
+    let (mut element, mut state) = View::<(), (), _, DynMessage>::build(&prev, &mut ctx);
+    ctx.backend_mut().take_ops();
+
+    let next = tile_layer(TILE_URL);
+    View::<(), (), _, DynMessage>::rebuild(&next, &prev, &mut state, &mut ctx, &mut element);
+    assert!(ctx.backend_mut().take_ops().is_empty());
+
+    let prev = next;
+    let next = tile_layer(OTHER_TILE_URL);
+    View::<(), (), _, DynMessage>::rebuild(&next, &prev, &mut state, &mut ctx, &mut element);
+    assert_eq!(
+        ctx.backend_mut().take_ops(),
+        vec![
+            BackendOp::RemoveLayer(LayerId(0)),
+            BackendOp::AddTileLayer {
+                id: LayerId(1),
+                url_template: OTHER_TILE_URL.to_string()
+            }
+        ]
+    );
+}
+
+#[test]
+fn tear_down_keyed_children_of_either() {
+    let mut ctx = MapCtx::with_backend(RecordingBackend::new());
+    let prev: Either<Keyed<u32, Marker>, TileLayer> =
+        Either::Left(markers(&[(1, 0.0, 0.0), (2, 1.0, 1.0)]));
+    let (mut element, mut state) = View::<(), (), _, DynMessage>::build(&prev, &mut ctx);
+    ctx.backend_mut().take_ops();
+
+    let next = Either::Right(tile_layer(TILE_URL));
+    View::<(), (), _, DynMessage>::rebuild(&next, &prev, &mut state, &mut ctx, &mut element);
+    let mut ops = ctx.backend_mut().take_ops();
+    // The order of the removed keyed children is not specified.
+    ops[..2].sort_by_key(|op| format!("{op:?}"));
+    assert_eq!(
+        ops,
+        vec![
+            BackendOp::RemoveLayer(LayerId(0)),
+            BackendOp::RemoveLayer(LayerId(1)),
+            BackendOp::AddTileLayer {
+                id: LayerId(2),
+                url_template: TILE_URL.to_string()
+            }
+        ]
+    );
+
+    View::<(), (), _, DynMessage>::teardown(&next, &mut state, &mut ctx, &mut element);
+    assert_eq!(
+        ctx.backend_mut().take_ops(),
+        vec![BackendOp::RemoveLayer(LayerId(2))]
+    );
+}